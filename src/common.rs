@@ -1,8 +1,14 @@
+pub mod aabb;
+pub mod mat4;
+pub mod onb;
 pub mod random;
 pub mod ray;
 pub mod vec3;
 
 pub use std::f64::consts::PI;
 
+pub use aabb::*;
+pub use mat4::*;
+pub use onb::*;
 pub use ray::*;
 pub use vec3::*;
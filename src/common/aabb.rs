@@ -0,0 +1,126 @@
+use crate::common::{ray::Ray, vec3::*};
+
+/// Axis-aligned bounding box. An empty box has `min` above `max` so that it
+/// acts as the identity for `union`.
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Default for Aabb {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl Aabb {
+    pub const fn empty() -> Self {
+        Self {
+            min: Vec3(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: Vec3(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+
+    /// The box spanning both points, in any order
+    pub fn new(a: Point, b: Point) -> Self {
+        Self {
+            min: a.min(&b),
+            max: a.max(&b),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.0 > self.max.0 || self.min.1 > self.max.1 || self.min.2 > self.max.2
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.min(&other.min),
+            max: self.max.max(&other.max),
+        }
+    }
+
+    pub fn union_point(&self, point: &Point) -> Self {
+        Self {
+            min: self.min.min(point),
+            max: self.max.max(point),
+        }
+    }
+
+    /// Grows every axis thinner than `delta` to that width, so that planar
+    /// objects don't get degenerate boxes
+    pub fn padded(&self, delta: f64) -> Self {
+        let mut min = self.min;
+        let mut max = self.max;
+        for (lo, hi) in [
+            (&mut min.0, &mut max.0),
+            (&mut min.1, &mut max.1),
+            (&mut min.2, &mut max.2),
+        ] {
+            if *hi - *lo < delta {
+                *lo -= delta / 2.0;
+                *hi += delta / 2.0;
+            }
+        }
+        Self { min, max }
+    }
+
+    pub fn diagonal(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    pub fn centroid(&self) -> Point {
+        0.5 * (self.min + self.max)
+    }
+
+    pub fn longest_axis(&self) -> usize {
+        let d = self.diagonal();
+        if d.0 > d.1 && d.0 > d.2 {
+            0
+        } else if d.1 > d.2 {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let d = self.diagonal();
+        2.0 * (d.0 * d.1 + d.1 * d.2 + d.2 * d.0)
+    }
+
+    /// Slab test. `inv_dir` is the componentwise reciprocal of the ray direction,
+    /// precomputed once per ray by the caller.
+    pub fn hit(&self, ray: &Ray, inv_dir: &Vec3, t_min: f64, t_max: f64) -> bool {
+        self.clip(ray, inv_dir, t_min, t_max).is_some()
+    }
+
+    /// The part of `t_min..t_max` over which the ray is inside the box
+    pub fn clip(
+        &self,
+        ray: &Ray,
+        inv_dir: &Vec3,
+        mut t_min: f64,
+        mut t_max: f64,
+    ) -> Option<(f64, f64)> {
+        for axis in 0..3 {
+            let t0 = (self.min[axis] - ray.origin[axis]) * inv_dir[axis];
+            let t1 = (self.max[axis] - ray.origin[axis]) * inv_dir[axis];
+            let (t0, t1) = if inv_dir[axis] < 0.0 {
+                (t1, t0)
+            } else {
+                (t0, t1)
+            };
+            t_min = t_min.max(t0);
+            t_max = t_max.min(t1);
+            if t_max < t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+}
@@ -0,0 +1,44 @@
+use crate::common::vec3::*;
+
+/// Orthonormal basis whose `w` axis points along a given direction
+#[derive(Debug, Clone, Copy)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    pub fn new(w: &Vec3) -> Self {
+        let w = w.unit_vec();
+        let a = if w.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = w.cross(&a).unit_vec();
+        let u = w.cross(&v);
+        Self { u, v, w }
+    }
+
+    /// Basis whose `u` axis is `tangent` projected onto the plane normal to
+    /// `w`, `None` if `tangent` is too close to parallel to `w`
+    pub fn with_tangent(w: &Vec3, tangent: &Vec3) -> Option<Self> {
+        let w = w.unit_vec();
+        let u = *tangent - tangent.dot(&w) * w;
+        if u.near_zero() {
+            return None;
+        }
+        let u = u.unit_vec();
+        Some(Self {
+            u,
+            v: w.cross(&u),
+            w,
+        })
+    }
+
+    /// Converts coordinates in this basis to world space
+    pub fn local(&self, a: &Vec3) -> Vec3 {
+        a.x() * self.u + a.y() * self.v + a.z() * self.w
+    }
+}
@@ -1,3 +1,4 @@
+use rand::distributions::Distribution;
 use rand::Rng;
 use rand::{rngs::SmallRng, SeedableRng};
 use std::cell::RefCell;
@@ -6,6 +7,31 @@ thread_local! {
     static RNG: RefCell<SmallRng> = RefCell::new(SmallRng::from_entropy());
 }
 
+/// Reseeds the calling thread's generator
+pub fn seed(seed: u64) {
+    RNG.with(|gen| *gen.borrow_mut() = SmallRng::seed_from_u64(seed));
+}
+
+/// A seed drawn from system entropy
+pub fn entropy_seed() -> u64 {
+    rand::random()
+}
+
+/// SplitMix64's finalizer, a bijection that scrambles every input bit
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Seed for an independent stream identified by `indices`, e.g. a pixel and
+/// sample number, so results don't depend on which thread draws them
+pub fn derive_seed(seed: u64, indices: &[u64]) -> u64 {
+    indices.iter().fold(mix(seed), |hash, &index| {
+        mix(hash ^ mix(index.wrapping_add(0x9e37_79b9_7f4a_7c15)))
+    })
+}
+
 /// exclusive range
 pub fn range(lower: f64, upper: f64) -> f64 {
     RNG.with(|gen| {
@@ -17,3 +43,7 @@ pub fn range(lower: f64, upper: f64) -> f64 {
 pub fn unit() -> f64 {
     range(0.0, 1.0)
 }
+
+pub fn sample<T>(distribution: impl Distribution<T>) -> T {
+    RNG.with(|gen| distribution.sample(&mut *gen.borrow_mut()))
+}
@@ -1,17 +1,37 @@
-use crate::vec3::*;
+use crate::common::vec3::*;
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone, Copy)]
 pub struct Ray {
     pub origin: Point,
     pub direction: Vec3,
+    /// Moment within the camera's shutter interval the ray exists at, used
+    /// to place moving objects
+    pub time: f64,
 }
 
 impl Ray {
     pub fn new(origin: Point, direction: Vec3) -> Self {
-        Self { origin, direction }
+        Self::at_time(origin, direction, 0.0)
+    }
+
+    pub fn at_time(origin: Point, direction: Vec3, time: f64) -> Self {
+        Self {
+            origin,
+            direction,
+            time,
+        }
     }
 
     pub fn at(&self, t: f64) -> Point {
         self.origin + t * self.direction
     }
+
+    /// Componentwise reciprocal of the direction, for slab tests against boxes
+    pub fn inv_direction(&self) -> Vec3 {
+        Vec3::new(
+            1.0 / self.direction.x(),
+            1.0 / self.direction.y(),
+            1.0 / self.direction.z(),
+        )
+    }
 }
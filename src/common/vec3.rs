@@ -1,10 +1,12 @@
-use crate::random;
+use crate::common::random;
+use serde::{Deserialize, Serialize};
 use std::{
+    f64::consts::PI,
     fmt::Display,
-    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    ops::{Add, AddAssign, Div, DivAssign, Index, Mul, MulAssign, Neg, Sub, SubAssign},
 };
 
-#[derive(Default, Debug, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Vec3(pub f64, pub f64, pub f64);
 
 impl Vec3 {
@@ -50,6 +52,21 @@ impl Vec3 {
         *self / self.length()
     }
 
+    pub fn min(&self, other: &Self) -> Self {
+        Self(
+            self.0.min(other.0),
+            self.1.min(other.1),
+            self.2.min(other.2),
+        )
+    }
+    pub fn max(&self, other: &Self) -> Self {
+        Self(
+            self.0.max(other.0),
+            self.1.max(other.1),
+            self.2.max(other.2),
+        )
+    }
+
     pub fn random_unit() -> Self {
         Self::random(0.0, 1.0)
     }
@@ -79,6 +96,14 @@ impl Vec3 {
             -point
         }
     }
+    /// Direction around +z with density cos(theta) / pi
+    pub fn random_cosine_direction() -> Self {
+        let r1 = random::unit();
+        let r2 = random::unit();
+        let phi = 2.0 * PI * r1;
+        let r = r2.sqrt();
+        Self(phi.cos() * r, phi.sin() * r, (1.0 - r2).sqrt())
+    }
     pub fn random_in_unit_disk() -> Self {
         loop {
             let point = Self(random::range(-1.0, 1.0), random::range(-1.0, 1.0), 0.0);
@@ -102,6 +127,15 @@ impl Vec3 {
         let near_zero = |f: f64| f.abs() < 1e-8;
         near_zero(self.0) && near_zero(self.1) && near_zero(self.2)
     }
+
+    pub fn max_component(&self) -> f64 {
+        self.0.max(self.1).max(self.2)
+    }
+
+    /// Relative luminance of a linear Rec. 709 color
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.0 + 0.7152 * self.1 + 0.0722 * self.2
+    }
 }
 
 impl Display for Vec3 {
@@ -110,6 +144,18 @@ impl Display for Vec3 {
     }
 }
 
+impl Index<usize> for Vec3 {
+    type Output = f64;
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            0 => &self.0,
+            1 => &self.1,
+            2 => &self.2,
+            _ => panic!("Vec3 index out of range: {index}"),
+        }
+    }
+}
+
 impl AddAssign for Vec3 {
     fn add_assign(&mut self, rhs: Self) {
         self.0 += rhs.0;
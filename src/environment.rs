@@ -0,0 +1,24 @@
+pub mod gradient;
+pub mod map;
+pub mod sky;
+pub mod uniform;
+
+use crate::common::*;
+
+/// Light arriving from infinitely far away, seen by rays that escape the scene
+pub trait Environment: std::fmt::Debug + Send + Sync {
+    /// Radiance seen when looking along `direction`
+    fn radiance(&self, direction: &Vec3) -> Color;
+
+    /// Solid angle density with which `sample` picks `direction`. Zero for
+    /// environments that cannot be sampled.
+    fn pdf(&self, _direction: &Vec3) -> f64 {
+        0.0
+    }
+
+    /// Random direction towards the environment for direct lighting, `None`
+    /// if it isn't worth sampling
+    fn sample(&self) -> Option<Vec3> {
+        None
+    }
+}
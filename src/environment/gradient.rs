@@ -0,0 +1,29 @@
+use crate::{common::*, environment::Environment};
+
+/// Vertical blend from `horizon` looking straight down to `zenith` looking
+/// straight up
+#[derive(Debug, Clone, Copy)]
+pub struct Gradient {
+    pub horizon: Color,
+    pub zenith: Color,
+}
+
+impl Gradient {
+    pub fn new(horizon: Color, zenith: Color) -> Self {
+        Self { horizon, zenith }
+    }
+}
+
+/// The sky of Ray Tracing in One Weekend
+impl Default for Gradient {
+    fn default() -> Self {
+        Self::new(Color::white(), Color::new(0.5, 0.7, 1.0))
+    }
+}
+
+impl Environment for Gradient {
+    fn radiance(&self, direction: &Vec3) -> Color {
+        let alpha = 0.5 * (direction.unit_vec().y() + 1.0);
+        (1.0 - alpha) * self.horizon + alpha * self.zenith
+    }
+}
@@ -0,0 +1,144 @@
+use std::{f64::consts::PI, path::Path, sync::Arc};
+
+use crate::{
+    common::*,
+    environment::Environment,
+    texture::image::{Image, ImageError},
+};
+
+/// Piecewise constant density over [0, 1) with one piece per weight
+#[derive(Debug)]
+struct Distribution {
+    /// Running sums of the weights scaled to end at 1, starting with 0
+    cdf: Vec<f64>,
+    total: f64,
+}
+
+impl Distribution {
+    /// Uniform if all weights are zero
+    fn new(weights: impl Iterator<Item = f64>) -> Self {
+        let mut cdf = vec![0.0];
+        for weight in weights {
+            cdf.push(cdf[cdf.len() - 1] + weight);
+        }
+        let total = cdf[cdf.len() - 1];
+        let pieces = (cdf.len() - 1) as f64;
+        for (i, value) in cdf.iter_mut().enumerate() {
+            *value = if total > 0.0 {
+                *value / total
+            } else {
+                i as f64 / pieces
+            };
+        }
+        Self { cdf, total }
+    }
+
+    fn len(&self) -> usize {
+        self.cdf.len() - 1
+    }
+
+    /// Index of the piece containing a random position, and the position
+    fn sample(&self) -> (usize, f64) {
+        let xi = random::unit();
+        let index = (self.cdf.partition_point(|&c| c <= xi) - 1).min(self.len() - 1);
+        let (low, high) = (self.cdf[index], self.cdf[index + 1]);
+        let offset = ((xi - low) / (high - low)).clamp(0.0, 1.0);
+        (index, (index as f64 + offset) / self.len() as f64)
+    }
+
+    /// Density over [0, 1) inside piece `index`
+    fn pdf(&self, index: usize) -> f64 {
+        (self.cdf[index + 1] - self.cdf[index]) * self.len() as f64
+    }
+}
+
+/// Equirectangular image wrapped around the scene: the top row is straight
+/// up, the middle column looks along +x and columns run counterclockwise
+/// seen from above, like the texture coordinates of a sphere. Directions are
+/// sampled in proportion to the luminance they see.
+#[derive(Debug)]
+pub struct EnvironmentMap {
+    image: Arc<Image>,
+    intensity: f64,
+    to_world: Mat4,
+    to_local: Mat4,
+    /// Picks a row, `None` if the map is black
+    rows: Option<Distribution>,
+    /// Picks a column within each row
+    columns: Vec<Distribution>,
+}
+
+impl EnvironmentMap {
+    /// The map turned counterclockwise seen from above by `rotation` degrees,
+    /// with its radiance multiplied by `intensity`
+    pub fn new(image: Arc<Image>, rotation: f64, intensity: f64) -> Self {
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let (width, height) = (image.width, image.height);
+        // Rows near the poles cover less solid angle than the ones around the
+        // horizon
+        let columns: Vec<Distribution> = (0..height)
+            .map(|y| {
+                let sin_theta = (PI * (y as f64 + 0.5) / height as f64).sin();
+                Distribution::new(
+                    (0..width).map(|x| image.pixel(x, y).luminance().max(0.0) * sin_theta),
+                )
+            })
+            .collect();
+        let rows = Distribution::new(columns.iter().map(|row| row.total));
+        Self {
+            image,
+            intensity,
+            to_world: Mat4::rotation(&up, rotation),
+            to_local: Mat4::rotation(&up, -rotation),
+            rows: (rows.total > 0.0 && intensity > 0.0).then_some(rows),
+            columns,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>, rotation: f64, intensity: f64) -> Result<Self, ImageError> {
+        Ok(Self::new(Arc::new(Image::load(path)?), rotation, intensity))
+    }
+
+    /// Pixel seen along the unit `direction` in map space
+    fn pixel_at(&self, direction: &Vec3) -> (usize, usize) {
+        let u = (f64::atan2(-direction.z(), direction.x()) + PI) / (2.0 * PI);
+        let v = direction.y().clamp(-1.0, 1.0).acos() / PI;
+        let x = ((u * self.image.width as f64) as usize).min(self.image.width - 1);
+        let y = ((v * self.image.height as f64) as usize).min(self.image.height - 1);
+        (x, y)
+    }
+}
+
+impl Environment for EnvironmentMap {
+    fn radiance(&self, direction: &Vec3) -> Color {
+        if self.image.pixels.is_empty() {
+            return Color::black();
+        }
+        let (x, y) = self.pixel_at(&self.to_local.transform_vector(&direction.unit_vec()));
+        self.intensity * self.image.pixel(x, y)
+    }
+
+    /// The density over the image divided by the Jacobian of mapping it onto
+    /// the sphere, 2π² sin θ
+    fn pdf(&self, direction: &Vec3) -> f64 {
+        let Some(rows) = &self.rows else {
+            return 0.0;
+        };
+        let local = self.to_local.transform_vector(&direction.unit_vec());
+        let sin_theta = (1.0 - local.y() * local.y()).max(0.0).sqrt();
+        if sin_theta == 0.0 {
+            return 0.0;
+        }
+        let (x, y) = self.pixel_at(&local);
+        rows.pdf(y) * self.columns[y].pdf(x) / (2.0 * PI * PI * sin_theta)
+    }
+
+    fn sample(&self) -> Option<Vec3> {
+        let (y, v) = self.rows.as_ref()?.sample();
+        let (_, u) = self.columns[y].sample();
+        let phi = 2.0 * PI * u - PI;
+        let (sin_theta, cos_theta) = (PI * v).sin_cos();
+        let local = Vec3::new(sin_theta * phi.cos(), cos_theta, -sin_theta * phi.sin());
+        Some(self.to_world.transform_vector(&local))
+    }
+}
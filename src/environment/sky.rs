@@ -0,0 +1,206 @@
+use std::f64::consts::PI;
+
+use crate::{common::*, environment::Environment};
+
+/// Angular radius of the sun's disk
+const SUN_RADIUS: f64 = 0.004_65;
+/// Luminance of the sun's disk outside the atmosphere, in kcd/m²
+const SUN_LUMINANCE: f64 = 1.9e6;
+/// Luminance, in kcd/m², with a radiance of one: about that of a white
+/// surface in full sun
+const RADIANCE_UNIT: f64 = 25.0;
+
+/// Coefficients of the Perez distribution
+/// `(1 + A e^(B / cos θ)) (1 + C e^(D γ) + E cos² γ)` for a view direction
+/// at angle θ from the zenith and γ from the sun
+type Perez = [f64; 5];
+
+fn perez(coefficients: &Perez, cos_theta: f64, gamma: f64) -> f64 {
+    let [a, b, c, d, e] = *coefficients;
+    (1.0 + a * (b / cos_theta).exp()) * (1.0 + c * (d * gamma).exp() + e * gamma.cos().powi(2))
+}
+
+/// Value of the cubic in the sun's zenith angle whose coefficients are each
+/// quadratic in the turbidity, for the zenith chromaticities
+fn zenith_chromaticity(coefficients: [[f64; 4]; 3], turbidity: f64, theta: f64) -> f64 {
+    let powers = [theta.powi(3), theta.powi(2), theta, 1.0];
+    let [t2, t1, t0] =
+        coefficients.map(|row| row.iter().zip(powers).map(|(c, p)| c * p).sum::<f64>());
+    turbidity * turbidity * t2 + turbidity * t1 + t0
+}
+
+/// CIE xyY to linear sRGB
+fn xyy_to_rgb(x: f64, y: f64, luminance: f64) -> Color {
+    let (cx, cz) = (x / y * luminance, (1.0 - x - y) / y * luminance);
+    let r = 3.2406 * cx - 1.5372 * luminance - 0.4986 * cz;
+    let g = -0.9689 * cx + 1.8758 * luminance + 0.0415 * cz;
+    let b = 0.0557 * cx - 0.2040 * luminance + 1.0570 * cz;
+    Color::new(r.max(0.0), g.max(0.0), b.max(0.0))
+}
+
+/// Clear daytime sky of the Preetham model, with the sun's disk dimmed and
+/// reddened by Rayleigh and aerosol extinction. Below the horizon the sky is
+/// mirrored, as the model doesn't cover it. The sun is sampled for direct lighting,
+/// the smooth sky is left to scattering.
+#[derive(Debug, Clone)]
+pub struct Sky {
+    sun: Vec3,
+    /// Perez coefficients for the luminance and the x and y chromaticities
+    coefficients: [Perez; 3],
+    /// Luminance and chromaticities straight up, divided by the Perez
+    /// distribution there so that it scales to them
+    zenith: [f64; 3],
+    sun_radiance: Color,
+    /// `1 - cos` of the sun's angular radius
+    sun_cone: f64,
+    intensity: f64,
+}
+
+impl Sky {
+    /// Sun `sun_elevation` degrees above the horizon and `sun_azimuth`
+    /// degrees counterclockwise from +x seen from above. `turbidity` is the
+    /// haziness of the air, from 2 for very clear to 10 for hazy. Panics if
+    /// the sun is below the horizon or the turbidity outside that range.
+    pub fn new(sun_elevation: f64, sun_azimuth: f64, turbidity: f64) -> Self {
+        assert!(
+            (0.0..=90.0).contains(&sun_elevation),
+            "sun must be above the horizon"
+        );
+        assert!(
+            (2.0..=10.0).contains(&turbidity),
+            "turbidity must be between 2 and 10"
+        );
+        let t = turbidity;
+        let (elevation, azimuth) = (sun_elevation.to_radians(), sun_azimuth.to_radians());
+        let sun = Vec3::new(
+            elevation.cos() * azimuth.cos(),
+            elevation.sin(),
+            -elevation.cos() * azimuth.sin(),
+        );
+        let theta = PI / 2.0 - elevation;
+
+        let coefficients = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta);
+        let zenith_luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let zenith_x = zenith_chromaticity(
+            [
+                [0.00166, -0.00375, 0.00209, 0.0],
+                [-0.02903, 0.06377, -0.03202, 0.00394],
+                [0.11693, -0.21196, 0.06052, 0.25886],
+            ],
+            t,
+            theta,
+        );
+        let zenith_y = zenith_chromaticity(
+            [
+                [0.00275, -0.00610, 0.00317, 0.0],
+                [-0.04214, 0.08970, -0.04153, 0.00516],
+                [0.15346, -0.26756, 0.06670, 0.26688],
+            ],
+            t,
+            theta,
+        );
+        let zenith = [zenith_luminance, zenith_x, zenith_y];
+        let zenith = std::array::from_fn(|i| zenith[i] / perez(&coefficients[i], 1.0, theta));
+
+        // Optical depths of Rayleigh and aerosol scattering at wavelengths
+        // standing in for the red, green and blue channels, in micrometers,
+        // through the relative air mass in the sun's direction
+        let air_mass = 1.0 / (theta.cos() + 0.15 * (93.885 - theta.to_degrees()).powf(-1.253));
+        let beta = 0.046_083_658 * t - 0.045_860_259;
+        let transmittance = [0.65, 0.57, 0.475].map(|lambda: f64| {
+            let rayleigh = 0.008_735 * lambda.powf(-4.08);
+            let aerosol = beta * lambda.powf(-1.3);
+            (-(rayleigh + aerosol) * air_mass).exp()
+        });
+        let sun_radiance =
+            SUN_LUMINANCE * Color::new(transmittance[0], transmittance[1], transmittance[2]);
+
+        Self {
+            sun,
+            coefficients,
+            zenith,
+            sun_radiance,
+            sun_cone: 2.0 * (SUN_RADIUS / 2.0).sin().powi(2),
+            intensity: 1.0,
+        }
+    }
+
+    /// Multiplies the radiance of sky and sun
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Direction towards the center of the sun
+    pub fn sun(&self) -> Vec3 {
+        self.sun
+    }
+
+    fn in_sun(&self, direction: &Vec3) -> bool {
+        1.0 - direction.dot(&self.sun) <= self.sun_cone
+    }
+
+    fn sky(&self, direction: &Vec3) -> Color {
+        let direction = Vec3::new(direction.x(), direction.y().abs(), direction.z());
+        let cos_theta = direction.y();
+        let gamma = direction.dot(&self.sun).clamp(-1.0, 1.0).acos();
+        let [luminance, x, y] = std::array::from_fn(|i| {
+            self.zenith[i] * perez(&self.coefficients[i], cos_theta, gamma)
+        });
+        xyy_to_rgb(x, y, luminance)
+    }
+}
+
+impl Environment for Sky {
+    fn radiance(&self, direction: &Vec3) -> Color {
+        let direction = direction.unit_vec();
+        let mut radiance = self.sky(&direction);
+        if self.in_sun(&direction) {
+            radiance += self.sun_radiance;
+        }
+        (self.intensity / RADIANCE_UNIT) * radiance
+    }
+
+    fn pdf(&self, direction: &Vec3) -> f64 {
+        if self.intensity > 0.0 && self.in_sun(&direction.unit_vec()) {
+            1.0 / (2.0 * PI * self.sun_cone)
+        } else {
+            0.0
+        }
+    }
+
+    /// Uniformly within the sun's disk
+    fn sample(&self) -> Option<Vec3> {
+        if self.intensity <= 0.0 {
+            return None;
+        }
+        let phi = 2.0 * PI * random::unit();
+        let z = 1.0 - random::unit() * self.sun_cone;
+        let sin_theta = (1.0 - z * z).sqrt();
+        let direction = Vec3::new(phi.cos() * sin_theta, phi.sin() * sin_theta, z);
+        Some(Onb::new(&self.sun).local(&direction))
+    }
+}
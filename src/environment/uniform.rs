@@ -0,0 +1,25 @@
+use crate::{common::*, environment::Environment};
+
+/// The same radiance from every direction
+#[derive(Debug, Clone, Copy)]
+pub struct Uniform {
+    pub color: Color,
+}
+
+impl Uniform {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+
+    pub const fn black() -> Self {
+        Self {
+            color: Color::black(),
+        }
+    }
+}
+
+impl Environment for Uniform {
+    fn radiance(&self, _direction: &Vec3) -> Color {
+        self.color
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{common::*, output::ImageBuffer, tonemap::ToneMapping};
+
+/// Unclamped linear radiance accumulated per pixel, top row first
+#[derive(Debug, Clone)]
+pub struct Film {
+    pub width: usize,
+    pub height: usize,
+    sums: Vec<Color>,
+    samples: Vec<u32>,
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            sums: vec![Color::black(); width * height],
+            samples: vec![0; width * height],
+        }
+    }
+
+    pub fn add_sample(&mut self, x: usize, y: usize, radiance: Color) {
+        self.add_samples(x, y, radiance, 1);
+    }
+
+    /// Adds `count` samples whose radiance sums to `sum`
+    pub fn add_samples(&mut self, x: usize, y: usize, sum: Color, count: u32) {
+        let index = y * self.width + x;
+        self.sums[index] += sum;
+        self.samples[index] += count;
+    }
+
+    /// Mean radiance of the pixel, black if it has no samples yet
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        let index = y * self.width + x;
+        match self.samples[index] {
+            0 => Color::black(),
+            count => self.sums[index] / count as f64,
+        }
+    }
+
+    /// Tone maps and quantizes the film to 8-bit sRGB
+    pub fn develop(&self, tonemap: &ToneMapping) -> ImageBuffer {
+        let mut image = ImageBuffer::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                write_color(&mut image, x, y, &self.pixel(x, y), tonemap);
+            }
+        }
+        image
+    }
+}
+
+fn write_color(image: &mut ImageBuffer, x: usize, y: usize, color: &Color, tonemap: &ToneMapping) {
+    image.set(x, y, tonemap.map(color));
+}
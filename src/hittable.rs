@@ -1,7 +1,15 @@
+pub mod bvh;
+pub mod constant_medium;
+pub mod grid_medium;
 pub mod hit_list;
+pub mod mesh;
+pub mod moving_sphere;
+pub mod quad;
 pub mod sphere;
+pub mod transform;
+pub mod triangle;
 
-use crate::{material::Material, ray::Ray, vec3::*};
+use crate::{common::*, material::Material};
 use std::sync::Arc;
 
 #[derive(Debug)]
@@ -9,6 +17,8 @@ pub struct HitRecord {
     pub point: Point,
     pub normal: Vec3,
     pub time: f64,
+    pub u: f64,
+    pub v: f64,
     pub front_face: bool,
     pub material: Arc<dyn Material>,
 }
@@ -25,6 +35,8 @@ impl HitRecord {
         Self {
             point,
             time,
+            u: 0.0,
+            v: 0.0,
             material,
             front_face,
             normal: if front_face {
@@ -34,8 +46,47 @@ impl HitRecord {
             },
         }
     }
+
+    /// Scattering event inside a participating medium at `time` along `ray`.
+    /// There is no surface there, so the normal is arbitrary; facing the ray
+    /// keeps the record front facing.
+    pub fn in_medium(ray: &Ray, time: f64, phase_function: Arc<dyn Material>) -> Self {
+        let normal = -ray.direction.unit_vec();
+        Self::new(ray.at(time), time, phase_function, ray, &normal)
+    }
+
+    /// Surface coordinates used for texture lookups
+    pub fn with_uv(mut self, u: f64, v: f64) -> Self {
+        self.u = u;
+        self.v = v;
+        self
+    }
 }
 
 pub trait Hittable: std::fmt::Debug + Send + Sync {
     fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+    fn bounding_box(&self) -> Aabb;
+
+    /// Unbiased estimate of the fraction of light passing through the object
+    /// along `ray` between `t_min` and `t_max`: zero or one for surfaces,
+    /// anything in between for participating media
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        if self.hit(ray, t_min, t_max).is_some() {
+            0.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Solid angle density, as seen from `origin` at `time`, with which
+    /// `random` picks `direction`. Zero for objects that cannot be sampled.
+    fn pdf_value(&self, _origin: &Point, _direction: &Vec3, _time: f64) -> f64 {
+        0.0
+    }
+
+    /// Direction from `origin` towards a random point on the object as it is
+    /// at `time`
+    fn random(&self, _origin: &Point, _time: f64) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
 }
@@ -0,0 +1,288 @@
+use crate::hittable::{hit_list::HitList, *};
+use std::sync::Arc;
+
+/// Number of centroid buckets evaluated when choosing a split plane
+const BIN_COUNT: usize = 12;
+/// Leaves are only formed above this size if SAH says a split is not worth it
+const MAX_LEAF_SIZE: usize = 4;
+/// Cost of visiting an interior node relative to one primitive intersection
+const TRAVERSAL_COST: f64 = 0.125;
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, Copy)]
+enum NodeKind {
+    Leaf {
+        first: usize,
+        count: usize,
+    },
+    /// The first child immediately follows its parent in `nodes`
+    Interior {
+        second: usize,
+        axis: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    bbox: Aabb,
+    kind: NodeKind,
+}
+
+struct Primitive {
+    object: Arc<dyn Hittable>,
+    bbox: Aabb,
+    centroid: Point,
+}
+
+/// Bounding volume hierarchy built with the surface area heuristic,
+/// stored as a flat depth-first array of nodes.
+#[derive(Debug, Default)]
+pub struct Bvh {
+    objects: Vec<Arc<dyn Hittable>>,
+    nodes: Vec<Node>,
+}
+
+impl Bvh {
+    pub fn new(objects: Vec<Arc<dyn Hittable>>) -> Self {
+        let mut prims: Vec<Primitive> = objects
+            .into_iter()
+            .map(|object| {
+                let bbox = object.bounding_box();
+                Primitive {
+                    object,
+                    bbox,
+                    centroid: bbox.centroid(),
+                }
+            })
+            .collect();
+        let mut bvh = Self {
+            objects: Vec::with_capacity(prims.len()),
+            nodes: Vec::with_capacity(2 * prims.len()),
+        };
+        if !prims.is_empty() {
+            bvh.build(&mut prims, 0);
+        }
+        bvh
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    fn push_leaf(&mut self, prims: &[Primitive], bbox: Aabb, index: usize) {
+        let first = self.objects.len();
+        self.objects
+            .extend(prims.iter().map(|prim| prim.object.clone()));
+        self.nodes[index] = Node {
+            bbox,
+            kind: NodeKind::Leaf {
+                first,
+                count: prims.len(),
+            },
+        };
+    }
+
+    fn build(&mut self, prims: &mut [Primitive], depth: usize) -> usize {
+        let index = self.nodes.len();
+        let bbox = prims
+            .iter()
+            .fold(Aabb::empty(), |acc, prim| acc.union(&prim.bbox));
+        self.nodes.push(Node {
+            bbox,
+            kind: NodeKind::Leaf { first: 0, count: 0 },
+        });
+
+        if prims.len() == 1 || depth >= MAX_DEPTH {
+            self.push_leaf(prims, bbox, index);
+            return index;
+        }
+
+        let centroid_bounds = prims
+            .iter()
+            .fold(Aabb::empty(), |acc, prim| acc.union_point(&prim.centroid));
+        let axis = centroid_bounds.longest_axis();
+        let lower = centroid_bounds.min[axis];
+        let extent = centroid_bounds.max[axis] - lower;
+        if extent <= 0.0 {
+            // Every centroid coincides, so no plane can separate them
+            self.push_leaf(prims, bbox, index);
+            return index;
+        }
+
+        let bin_of = |prim: &Primitive| {
+            let offset = (prim.centroid[axis] - lower) / extent;
+            ((offset * BIN_COUNT as f64) as usize).min(BIN_COUNT - 1)
+        };
+        let mut bins = [(0usize, Aabb::empty()); BIN_COUNT];
+        for prim in prims.iter() {
+            let bin = &mut bins[bin_of(prim)];
+            bin.0 += 1;
+            bin.1 = bin.1.union(&prim.bbox);
+        }
+
+        // Sweep from the right to get the area and count of every suffix,
+        // then from the left to evaluate each split.
+        let mut right_costs = [0.0; BIN_COUNT];
+        let (mut count, mut bounds) = (0, Aabb::empty());
+        for split in (1..BIN_COUNT).rev() {
+            count += bins[split].0;
+            bounds = bounds.union(&bins[split].1);
+            right_costs[split] = count as f64 * bounds.surface_area();
+        }
+        let (mut count, mut bounds) = (0, Aabb::empty());
+        let mut best = (f64::INFINITY, 0);
+        for split in 1..BIN_COUNT {
+            count += bins[split - 1].0;
+            bounds = bounds.union(&bins[split - 1].1);
+            let cost = count as f64 * bounds.surface_area() + right_costs[split];
+            if cost < best.0 {
+                best = (cost, split);
+            }
+        }
+        let split_cost = TRAVERSAL_COST + best.0 / bbox.surface_area();
+        let leaf_cost = prims.len() as f64;
+        if prims.len() <= MAX_LEAF_SIZE && split_cost >= leaf_cost {
+            self.push_leaf(prims, bbox, index);
+            return index;
+        }
+
+        let mut mid = partition(prims, |prim| bin_of(prim) < best.1);
+        if mid == 0 || mid == prims.len() {
+            mid = prims.len() / 2;
+            prims.select_nth_unstable_by(mid, |a, b| a.centroid[axis].total_cmp(&b.centroid[axis]));
+        }
+        let (left, right) = prims.split_at_mut(mid);
+        self.build(left, depth + 1);
+        let second = self.build(right, depth + 1);
+        self.nodes[index].kind = NodeKind::Interior { second, axis };
+        index
+    }
+}
+
+/// Moves every element matching `pred` to the front, returning how many matched
+fn partition<T>(items: &mut [T], pred: impl Fn(&T) -> bool) -> usize {
+    let mut mid = 0;
+    for i in 0..items.len() {
+        if pred(&items[i]) {
+            items.swap(i, mid);
+            mid += 1;
+        }
+    }
+    mid
+}
+
+impl From<HitList> for Bvh {
+    fn from(list: HitList) -> Self {
+        Self::new(list.objects)
+    }
+}
+
+impl Bvh {
+    /// Closest hit along with the object that was hit
+    pub fn hit_object(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<(HitRecord, &Arc<dyn Hittable>)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let inv_dir = ray.inv_direction();
+
+        let mut res = None;
+        let mut closest = t_max;
+        let mut stack = [0usize; MAX_DEPTH + 1];
+        let mut stack_len = 0;
+        let mut index = 0;
+        loop {
+            let node = &self.nodes[index];
+            if node.bbox.hit(ray, &inv_dir, t_min, closest) {
+                match node.kind {
+                    NodeKind::Leaf { first, count } => {
+                        for object in &self.objects[first..first + count] {
+                            if let Some(record) = object.hit(ray, t_min, closest) {
+                                closest = record.time;
+                                res = Some((record, object));
+                            }
+                        }
+                    }
+                    NodeKind::Interior { second, axis } => {
+                        // Visit the child nearer to the ray origin first so that
+                        // `closest` shrinks as early as possible
+                        let (near, far) = if inv_dir[axis] < 0.0 {
+                            (second, index + 1)
+                        } else {
+                            (index + 1, second)
+                        };
+                        stack[stack_len] = far;
+                        stack_len += 1;
+                        index = near;
+                        continue;
+                    }
+                }
+            }
+            if stack_len == 0 {
+                break;
+            }
+            stack_len -= 1;
+            index = stack[stack_len];
+        }
+        res
+    }
+}
+
+impl Hittable for Bvh {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.hit_object(ray, t_min, t_max).map(|(record, _)| record)
+    }
+
+    /// Visits every object the segment passes near, in no particular order,
+    /// stopping once something opaque is found
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        if self.nodes.is_empty() {
+            return 1.0;
+        }
+        let inv_dir = ray.inv_direction();
+
+        let mut transmittance = 1.0;
+        let mut stack = [0usize; MAX_DEPTH + 1];
+        let mut stack_len = 0;
+        let mut index = 0;
+        loop {
+            let node = &self.nodes[index];
+            if node.bbox.hit(ray, &inv_dir, t_min, t_max) {
+                match node.kind {
+                    NodeKind::Leaf { first, count } => {
+                        for object in &self.objects[first..first + count] {
+                            transmittance *= object.transmittance(ray, t_min, t_max);
+                            if transmittance == 0.0 {
+                                return 0.0;
+                            }
+                        }
+                    }
+                    NodeKind::Interior { second, .. } => {
+                        stack[stack_len] = second;
+                        stack_len += 1;
+                        index += 1;
+                        continue;
+                    }
+                }
+            }
+            if stack_len == 0 {
+                break;
+            }
+            stack_len -= 1;
+            index = stack[stack_len];
+        }
+        transmittance
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.nodes.first().map_or(Aabb::empty(), |node| node.bbox)
+    }
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use crate::{
+    common::*,
+    hittable::{HitRecord, Hittable},
+    material::{henyey_greenstein::HenyeyGreenstein, Material},
+};
+
+/// Samples where a ray scatters in a homogeneous medium of `density` between
+/// the ray parameters `t_min` and `t_max`, `None` if it passes through. Free
+/// flight distances are exponentially distributed, so the chance of passing is
+/// the transmittance of the segment.
+fn scatter_event(
+    density: f64,
+    phase_function: &Arc<dyn Material>,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<HitRecord> {
+    let length = ray.direction.length();
+    let distance = -(1.0 - random::unit()).ln() / density;
+    if distance >= (t_max - t_min) * length {
+        return None;
+    }
+    let time = t_min + distance / length;
+    Some(HitRecord::in_medium(ray, time, phase_function.clone()))
+}
+
+/// Participating medium of constant density filling a closed, convex
+/// boundary, like fog or smoke. Rays entering it scatter at a random depth
+/// according to the phase function.
+#[derive(Debug)]
+pub struct ConstantMedium {
+    boundary: Arc<dyn Hittable>,
+    density: f64,
+    phase_function: Arc<dyn Material>,
+}
+
+impl ConstantMedium {
+    pub fn new(
+        boundary: Arc<dyn Hittable>,
+        density: f64,
+        phase_function: Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            boundary,
+            density,
+            phase_function,
+        }
+    }
+
+    pub fn isotropic(boundary: Arc<dyn Hittable>, density: f64, albedo: Color) -> Self {
+        Self::new(
+            boundary,
+            density,
+            Arc::new(HenyeyGreenstein::isotropic(albedo)),
+        )
+    }
+
+    /// The part of `t_min..t_max` over which the ray is inside the boundary
+    fn inside(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        // Where the ray's line enters and leaves the boundary, which may be
+        // behind its origin when it starts inside
+        let enter = self.boundary.hit(ray, f64::NEG_INFINITY, f64::INFINITY)?;
+        let leave = self.boundary.hit(ray, enter.time + 0.0001, f64::INFINITY)?;
+        let t_enter = enter.time.max(t_min);
+        let t_leave = leave.time.min(t_max);
+        (t_enter < t_leave).then_some((t_enter, t_leave))
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t_enter, t_leave) = self.inside(ray, t_min, t_max)?;
+        scatter_event(self.density, &self.phase_function, ray, t_enter, t_leave)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+
+    /// Known exactly, by Beer-Lambert's law
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        self.inside(ray, t_min, t_max)
+            .map_or(1.0, |(t_enter, t_leave)| {
+                (-self.density * (t_leave - t_enter) * ray.direction.length()).exp()
+            })
+    }
+}
+
+/// Homogeneous medium filling the space between surfaces. Rays escaping the
+/// scene reach the environment unattenuated, as if the atmosphere were already
+/// part of it.
+#[derive(Debug)]
+pub struct Atmosphere {
+    density: f64,
+    phase_function: Arc<dyn Material>,
+}
+
+impl Atmosphere {
+    pub fn new(density: f64, phase_function: Arc<dyn Material>) -> Self {
+        Self {
+            density,
+            phase_function,
+        }
+    }
+
+    pub fn isotropic(density: f64, albedo: Color) -> Self {
+        Self::new(density, Arc::new(HenyeyGreenstein::isotropic(albedo)))
+    }
+
+    /// Fraction of light passing through `distance` of the atmosphere
+    pub fn transmittance(&self, distance: f64) -> f64 {
+        (-self.density * distance).exp()
+    }
+
+    /// Where `ray` scatters before reaching the surface hit at `surface`, if
+    /// it does
+    pub fn scatter(&self, ray: &Ray, t_min: f64, surface: &HitRecord) -> Option<HitRecord> {
+        scatter_event(self.density, &self.phase_function, ray, t_min, surface.time)
+    }
+}
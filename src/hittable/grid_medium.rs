@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use crate::{
+    common::*,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    volume::VoxelGrid,
+};
+
+/// Participating medium whose density varies over a voxel grid, like smoke or
+/// clouds. Free flights are tracked against the grid's maximum density, with
+/// the surplus made up of null collisions that leave the ray unchanged.
+#[derive(Debug)]
+pub struct GridMedium {
+    grid: Arc<VoxelGrid>,
+    /// Multiplies the densities stored in the grid
+    scale: f64,
+    phase_function: Arc<dyn Material>,
+    /// Upper bound of the scaled density
+    majorant: f64,
+}
+
+impl GridMedium {
+    pub fn new(grid: Arc<VoxelGrid>, scale: f64, phase_function: Arc<dyn Material>) -> Self {
+        let majorant = grid.max_density() * scale;
+        Self {
+            grid,
+            scale,
+            phase_function,
+            majorant,
+        }
+    }
+
+    fn density(&self, point: &Point) -> f64 {
+        self.grid.density(point) * self.scale
+    }
+
+    /// Ray parameters of the tentative collisions with the majorant medium
+    /// inside the grid between `t_min` and `t_max`
+    fn collisions(&self, ray: &Ray, t_min: f64, t_max: f64) -> impl Iterator<Item = f64> {
+        let inv_dir = ray.inv_direction();
+        let (t_enter, t_leave) = if self.majorant > 0.0 {
+            self.grid
+                .bounds()
+                .clip(ray, &inv_dir, t_min, t_max)
+                .unwrap_or((0.0, 0.0))
+        } else {
+            (0.0, 0.0)
+        };
+        let rate = self.majorant * ray.direction.length();
+        let mut t = t_enter;
+        std::iter::from_fn(move || {
+            t -= (1.0 - random::unit()).ln() / rate;
+            (t < t_leave).then_some(t)
+        })
+    }
+}
+
+impl Hittable for GridMedium {
+    /// Delta tracking: each tentative collision is a real scattering event
+    /// with probability of the local density over the majorant
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let time = self
+            .collisions(ray, t_min, t_max)
+            .find(|&t| random::unit() * self.majorant < self.density(&ray.at(t)))?;
+        Some(HitRecord::in_medium(ray, time, self.phase_function.clone()))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.grid.bounds()
+    }
+
+    /// Ratio tracking: the product of the null collision probabilities of all
+    /// tentative collisions, which has less variance than counting whether
+    /// delta tracking gets through
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        self.collisions(ray, t_min, t_max)
+            .map(|t| 1.0 - self.density(&ray.at(t)) / self.majorant)
+            .product()
+    }
+}
@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use crate::{
+    common::*,
+    hittable::{
+        bvh::Bvh,
+        triangle::{self, Uv},
+        HitRecord, Hittable,
+    },
+    material::Material,
+};
+
+/// Vertex attributes shared by every triangle of a mesh. Normals and UVs,
+/// when present, are indexed the same way as positions.
+#[derive(Debug)]
+struct MeshData {
+    positions: Vec<Point>,
+    normals: Option<Vec<Vec3>>,
+    uvs: Option<Vec<Uv>>,
+    indices: Vec<[usize; 3]>,
+    material: Arc<dyn Material>,
+    /// Running sum of face areas, for picking faces proportionally to area
+    area_cdf: Vec<f64>,
+}
+
+impl MeshData {
+    fn vertices(&self, face: usize) -> [Point; 3] {
+        self.indices[face].map(|i| self.positions[i])
+    }
+
+    fn total_area(&self) -> f64 {
+        self.area_cdf.last().copied().unwrap_or(0.0)
+    }
+}
+
+/// One face of a `TriangleMesh`, referring back into the shared vertex data
+#[derive(Debug)]
+struct MeshTriangle {
+    mesh: Arc<MeshData>,
+    face: usize,
+}
+
+impl Hittable for MeshTriangle {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let vertices = self.mesh.vertices(self.face);
+        let hit = triangle::intersect(&vertices, ray, t_min, t_max)?;
+        let indices = self.mesh.indices[self.face];
+        let normals = self.mesh.normals.as_ref().map(|n| indices.map(|i| n[i]));
+        let uvs = self.mesh.uvs.as_ref().map(|uv| indices.map(|i| uv[i]));
+        Some(triangle::record(
+            &vertices,
+            normals.as_ref(),
+            uvs.as_ref(),
+            &self.mesh.material,
+            ray,
+            hit,
+        ))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        triangle::bounding_box(&self.mesh.vertices(self.face))
+    }
+
+    /// Density of sampling this face as part of the whole mesh
+    fn pdf_value(&self, origin: &Point, direction: &Vec3, _time: f64) -> f64 {
+        let vertices = self.mesh.vertices(self.face);
+        triangle::solid_angle_pdf(&vertices, origin, direction, self.mesh.total_area())
+    }
+}
+
+/// Indexed triangle mesh with its own BVH over the faces
+#[derive(Debug)]
+pub struct TriangleMesh {
+    mesh: Arc<MeshData>,
+    bvh: Bvh,
+}
+
+impl TriangleMesh {
+    /// Panics if an index is out of range or an attribute array does not
+    /// match the number of positions.
+    pub fn new(
+        positions: Vec<Point>,
+        indices: Vec<[usize; 3]>,
+        normals: Option<Vec<Vec3>>,
+        uvs: Option<Vec<Uv>>,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        assert!(
+            indices.iter().flatten().all(|&i| i < positions.len()),
+            "mesh index out of range"
+        );
+        if let Some(normals) = &normals {
+            assert_eq!(normals.len(), positions.len(), "one normal per vertex");
+        }
+        if let Some(uvs) = &uvs {
+            assert_eq!(uvs.len(), positions.len(), "one uv per vertex");
+        }
+
+        let area_cdf = indices
+            .iter()
+            .scan(0.0, |total, face| {
+                *total += triangle::area(&face.map(|i| positions[i]));
+                Some(*total)
+            })
+            .collect();
+        let mesh = Arc::new(MeshData {
+            positions,
+            normals,
+            uvs,
+            indices,
+            material,
+            area_cdf,
+        });
+        let faces = (0..mesh.indices.len())
+            .map(|face| {
+                Arc::new(MeshTriangle {
+                    mesh: mesh.clone(),
+                    face,
+                }) as Arc<dyn Hittable>
+            })
+            .collect();
+        Self {
+            mesh,
+            bvh: Bvh::new(faces),
+        }
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.mesh.indices.len()
+    }
+
+    pub fn material(&self) -> &Arc<dyn Material> {
+        &self.mesh.material
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.bvh.hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bvh.bounding_box()
+    }
+
+    /// Sums over every face along `direction`, since faces behind the nearest
+    /// one could have been sampled as well
+    fn pdf_value(&self, origin: &Point, direction: &Vec3, time: f64) -> f64 {
+        let ray = Ray::at_time(*origin, *direction, time);
+        let mut pdf = 0.0;
+        let mut t_min = 0.001;
+        while let Some((rec, face)) = self.bvh.hit_object(&ray, t_min, f64::INFINITY) {
+            pdf += face.pdf_value(origin, direction, time);
+            t_min = rec.time + 0.001;
+        }
+        pdf
+    }
+
+    /// Picks a face with probability proportional to its area
+    fn random(&self, origin: &Point, _time: f64) -> Vec3 {
+        let target = random::unit() * self.mesh.total_area();
+        let face = self
+            .mesh
+            .area_cdf
+            .partition_point(|&sum| sum <= target)
+            .min(self.mesh.indices.len().saturating_sub(1));
+        triangle::sample(&self.mesh.vertices(face)) - *origin
+    }
+}
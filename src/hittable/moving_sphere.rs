@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use crate::{
+    common::*,
+    hittable::{sphere, HitRecord, Hittable},
+    material::Material,
+};
+
+/// Sphere whose center moves in a straight line from `center0` at time 0 to
+/// `center1` at time 1, resting at either end outside that interval
+#[derive(Debug)]
+pub struct MovingSphere {
+    pub center0: Point,
+    pub center1: Point,
+    pub radius: f64,
+    pub material: Arc<dyn Material>,
+}
+
+impl MovingSphere {
+    pub fn new(center0: Point, center1: Point, radius: f64, material: Arc<dyn Material>) -> Self {
+        Self {
+            center0,
+            center1,
+            radius,
+            material,
+        }
+    }
+
+    pub fn center(&self, time: f64) -> Point {
+        let time = time.clamp(0.0, 1.0);
+        self.center0 + time * (self.center1 - self.center0)
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let center = self.center(ray.time);
+        sphere::hit(&center, self.radius, &self.material, ray, t_min, t_max)
+    }
+
+    /// Covers the whole path of the sphere
+    fn bounding_box(&self) -> Aabb {
+        sphere::bounding_box(&self.center0, self.radius)
+            .union(&sphere::bounding_box(&self.center1, self.radius))
+    }
+
+    fn pdf_value(&self, origin: &Point, direction: &Vec3, time: f64) -> f64 {
+        sphere::cone_pdf(&self.center(time), self.radius, origin, direction)
+    }
+
+    fn random(&self, origin: &Point, time: f64) -> Vec3 {
+        sphere::sample_cone(&self.center(time), self.radius, origin)
+    }
+}
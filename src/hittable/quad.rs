@@ -0,0 +1,194 @@
+use std::sync::Arc;
+
+use crate::{
+    common::*,
+    hittable::{hit_list::HitList, HitRecord, Hittable},
+    material::Material,
+};
+
+/// Parallelogram spanned by the edges `u` and `v` from the corner `q`. The
+/// front face is the side `u × v` points to, and (u, v) texture coordinates
+/// run from 0 to 1 along the edges.
+#[derive(Debug)]
+pub struct Quad {
+    q: Point,
+    u: Vec3,
+    v: Vec3,
+    material: Arc<dyn Material>,
+    normal: Vec3,
+    /// Plane offset, `normal · p` for points `p` on the plane
+    d: f64,
+    /// `(u × v) / |u × v|²`, projecting plane points onto edge coordinates
+    w: Vec3,
+    area: f64,
+}
+
+impl Quad {
+    pub fn new(q: Point, u: Vec3, v: Vec3, material: Arc<dyn Material>) -> Self {
+        let n = u.cross(&v);
+        let normal = n.unit_vec();
+        Self {
+            q,
+            u,
+            v,
+            material,
+            normal,
+            d: normal.dot(&q),
+            w: n / n.length_squared(),
+            area: n.length(),
+        }
+    }
+
+    /// Rectangle in the plane z = `k`, facing +z
+    pub fn xy_rect(
+        x0: f64,
+        x1: f64,
+        y0: f64,
+        y1: f64,
+        k: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self::new(
+            Point::new(x0, y0, k),
+            Vec3::new(x1 - x0, 0.0, 0.0),
+            Vec3::new(0.0, y1 - y0, 0.0),
+            material,
+        )
+    }
+
+    /// Rectangle in the plane y = `k`, facing +y
+    pub fn xz_rect(
+        x0: f64,
+        x1: f64,
+        z0: f64,
+        z1: f64,
+        k: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self::new(
+            Point::new(x0, k, z0),
+            Vec3::new(0.0, 0.0, z1 - z0),
+            Vec3::new(x1 - x0, 0.0, 0.0),
+            material,
+        )
+    }
+
+    /// Rectangle in the plane x = `k`, facing +x
+    pub fn yz_rect(
+        y0: f64,
+        y1: f64,
+        z0: f64,
+        z1: f64,
+        k: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self::new(
+            Point::new(k, y0, z0),
+            Vec3::new(0.0, y1 - y0, 0.0),
+            Vec3::new(0.0, 0.0, z1 - z0),
+            material,
+        )
+    }
+
+    /// Reverses the front face, keeping the covered area
+    pub fn flipped(self) -> Self {
+        Self::new(self.q, self.v, self.u, self.material)
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let denom = self.normal.dot(&ray.direction);
+        if denom.abs() < 1e-12 {
+            return None;
+        }
+        let time = (self.d - self.normal.dot(&ray.origin)) / denom;
+        if time < t_min || t_max < time {
+            return None;
+        }
+        let point = ray.at(time);
+        let planar = point - self.q;
+        let alpha = self.w.dot(&planar.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+        Some(
+            HitRecord::new(point, time, self.material.clone(), ray, &self.normal)
+                .with_uv(alpha, beta),
+        )
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::new(self.q, self.q + self.u + self.v)
+            .union(&Aabb::new(self.q + self.u, self.q + self.v))
+            .padded(1e-4)
+    }
+
+    fn pdf_value(&self, origin: &Point, direction: &Vec3, _time: f64) -> f64 {
+        let Some(rec) = self.hit(&Ray::new(*origin, *direction), 0.001, f64::INFINITY) else {
+            return 0.0;
+        };
+        let distance_squared = rec.time * rec.time * direction.length_squared();
+        let cosine = self.normal.dot(direction).abs() / direction.length();
+        if cosine < 1e-8 {
+            return 0.0;
+        }
+        distance_squared / (cosine * self.area)
+    }
+
+    fn random(&self, origin: &Point, _time: f64) -> Vec3 {
+        self.q + random::unit() * self.u + random::unit() * self.v - *origin
+    }
+}
+
+/// Axis-aligned box made of six outward facing quads
+#[derive(Debug)]
+pub struct Cuboid {
+    sides: HitList,
+    bbox: Aabb,
+}
+
+impl Cuboid {
+    /// The box spanning two opposite corners, in any order
+    pub fn new(a: Point, b: Point, material: Arc<dyn Material>) -> Self {
+        let bbox = Aabb::new(a, b);
+        let (min, max) = (bbox.min, bbox.max);
+        let dx = Vec3::new(max.x() - min.x(), 0.0, 0.0);
+        let dy = Vec3::new(0.0, max.y() - min.y(), 0.0);
+        let dz = Vec3::new(0.0, 0.0, max.z() - min.z());
+
+        let mut sides = HitList::default();
+        let mut add = |q: Point, u: Vec3, v: Vec3| {
+            sides.add(Arc::new(Quad::new(q, u, v, material.clone())));
+        };
+        add(Point::new(min.x(), min.y(), max.z()), dx, dy); // front
+        add(Point::new(max.x(), min.y(), max.z()), -dz, dy); // right
+        add(Point::new(max.x(), min.y(), min.z()), -dx, dy); // back
+        add(Point::new(min.x(), min.y(), min.z()), dz, dy); // left
+        add(Point::new(min.x(), max.y(), max.z()), dx, -dz); // top
+        add(Point::new(min.x(), min.y(), min.z()), dx, dz); // bottom
+        Self {
+            sides,
+            bbox: bbox.padded(1e-4),
+        }
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.sides.hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn pdf_value(&self, origin: &Point, direction: &Vec3, time: f64) -> f64 {
+        self.sides.pdf_value(origin, direction, time)
+    }
+
+    fn random(&self, origin: &Point, time: f64) -> Vec3 {
+        self.sides.random(origin, time)
+    }
+}
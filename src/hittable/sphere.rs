@@ -1,12 +1,99 @@
 use std::sync::Arc;
 
 use crate::{
+    common::*,
     hittable::{HitRecord, Hittable},
     material::Material,
-    ray::Ray,
-    vec3::Point,
 };
 
+/// Spherical coordinates of a point on the unit sphere: `u` is the angle
+/// around the y axis starting from -x, `v` runs from the bottom pole to the top.
+fn uv(point: &Point) -> (f64, f64) {
+    let theta = (-point.y()).acos();
+    let phi = (-point.z()).atan2(point.x()) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
+
+/// Nearest intersection within `t_min..t_max` of `ray` with the sphere at
+/// `center`
+pub(super) fn hit(
+    center: &Point,
+    radius: f64,
+    material: &Arc<dyn Material>,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<HitRecord> {
+    let oc = ray.origin - *center;
+    let a = ray.direction.length_squared();
+    let h = oc.dot(&ray.direction);
+    let c = oc.length_squared() - radius * radius;
+
+    let discriminant = h * h - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let sqrtd = discriminant.sqrt();
+    let mut root = (-h - sqrtd) / a;
+    if root < t_min || t_max < root {
+        root = (-h + sqrtd) / a;
+        if root < t_min || t_max < root {
+            return None;
+        }
+    }
+
+    let time = root;
+    let point = ray.at(time);
+    let outward_normal = (point - *center) / radius;
+    let (u, v) = uv(&outward_normal);
+    Some(HitRecord::new(point, time, material.clone(), ray, &outward_normal).with_uv(u, v))
+}
+
+pub(super) fn bounding_box(center: &Point, radius: f64) -> Aabb {
+    let r = radius.abs();
+    let r = Vec3::new(r, r, r);
+    Aabb::new(*center - r, *center + r)
+}
+
+/// Cosine of the half angle of the cone the sphere subtends from `origin`,
+/// or `None` from inside the sphere
+fn cone_cos_theta_max(center: &Point, radius: f64, origin: &Point) -> Option<f64> {
+    let distance_squared = (*center - *origin).length_squared();
+    let radius_squared = radius * radius;
+    (distance_squared > radius_squared).then(|| (1.0 - radius_squared / distance_squared).sqrt())
+}
+
+/// Density of `direction` when sampling the cone the sphere subtends from
+/// `origin` uniformly
+pub(super) fn cone_pdf(center: &Point, radius: f64, origin: &Point, direction: &Vec3) -> f64 {
+    let Some(cos_theta_max) = cone_cos_theta_max(center, radius, origin) else {
+        return 0.0;
+    };
+    // Whether the direction lies inside the cone
+    let to_center = *center - *origin;
+    let along = to_center.dot(direction);
+    if along <= 0.0
+        || along * along
+            < cos_theta_max.powi(2) * to_center.length_squared() * direction.length_squared()
+    {
+        return 0.0;
+    }
+    1.0 / (2.0 * PI * (1.0 - cos_theta_max))
+}
+
+/// Samples the cone of directions the sphere subtends from `origin` uniformly
+pub(super) fn sample_cone(center: &Point, radius: f64, origin: &Point) -> Vec3 {
+    let Some(cos_theta_max) = cone_cos_theta_max(center, radius, origin) else {
+        return *center - *origin;
+    };
+    let phi = 2.0 * PI * random::unit();
+    let z = 1.0 + random::unit() * (cos_theta_max - 1.0);
+    let sin_theta = (1.0 - z * z).sqrt();
+    let direction = Vec3::new(phi.cos() * sin_theta, phi.sin() * sin_theta, z);
+    Onb::new(&(*center - *origin)).local(&direction)
+}
+
 #[derive(Debug)]
 pub struct Sphere {
     pub center: Point,
@@ -26,34 +113,18 @@ impl Sphere {
 
 impl Hittable for Sphere {
     fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let oc = ray.origin - self.center;
-        let a = ray.direction.length_squared();
-        let h = oc.dot(&ray.direction);
-        let c = oc.length_squared() - self.radius * self.radius;
+        hit(&self.center, self.radius, &self.material, ray, t_min, t_max)
+    }
 
-        let discriminant = h * h - a * c;
-        if discriminant < 0.0 {
-            return None;
-        }
+    fn bounding_box(&self) -> Aabb {
+        bounding_box(&self.center, self.radius)
+    }
 
-        let sqrtd = discriminant.sqrt();
-        let mut root = (-h - sqrtd) / a;
-        if root < t_min || t_max < root {
-            root = (-h + sqrtd) / a;
-            if root < t_min || t_max < root {
-                return None;
-            }
-        }
+    fn pdf_value(&self, origin: &Point, direction: &Vec3, _time: f64) -> f64 {
+        cone_pdf(&self.center, self.radius, origin, direction)
+    }
 
-        let time = root;
-        let point = ray.at(time);
-        let outward_normal = (point - self.center) / self.radius;
-        Some(HitRecord::new(
-            point,
-            time,
-            self.material.clone(),
-            ray,
-            &outward_normal,
-        ))
+    fn random(&self, origin: &Point, _time: f64) -> Vec3 {
+        sample_cone(&self.center, self.radius, origin)
     }
 }
@@ -0,0 +1,268 @@
+use std::{borrow::Cow, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::*,
+    hittable::{HitRecord, Hittable},
+};
+
+/// One step of an instance transform, written in scene files as e.g.
+/// `{ translate = [0, 1, 0] }`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum TransformStep {
+    Translate(Vec3),
+    /// Counterclockwise by `degrees` when looking down `axis`
+    Rotate {
+        axis: Vec3,
+        degrees: f64,
+    },
+    Scale(Vec3),
+}
+
+impl TransformStep {
+    pub fn matrix(&self) -> Mat4 {
+        match self {
+            TransformStep::Translate(offset) => Mat4::translation(offset),
+            TransformStep::Rotate { axis, degrees } => Mat4::rotation(axis, *degrees),
+            TransformStep::Scale(factors) => Mat4::scaling(factors),
+        }
+    }
+
+    /// The transform applying `steps` in order
+    pub fn compose(steps: &[TransformStep]) -> Mat4 {
+        steps
+            .iter()
+            .fold(Mat4::identity(), |matrix, step| step.matrix() * matrix)
+    }
+
+    /// The step a fraction `t` of the way from `self` to `other`, or `None`
+    /// if they are different kinds of step
+    pub fn lerp(&self, other: &Self, t: f64) -> Option<Self> {
+        let mix = |a: &Vec3, b: &Vec3| *a + t * (*b - *a);
+        match (self, other) {
+            (TransformStep::Translate(a), TransformStep::Translate(b)) => {
+                Some(TransformStep::Translate(mix(a, b)))
+            }
+            (
+                TransformStep::Rotate { axis, degrees },
+                TransformStep::Rotate {
+                    axis: axis1,
+                    degrees: degrees1,
+                },
+            ) => Some(TransformStep::Rotate {
+                axis: mix(axis, axis1),
+                degrees: degrees + t * (degrees1 - degrees),
+            }),
+            (TransformStep::Scale(a), TransformStep::Scale(b)) => {
+                Some(TransformStep::Scale(mix(a, b)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A transform with the matrices needed to move rays and hits between
+/// world and object space
+#[derive(Debug, Clone)]
+struct Placement {
+    transform: Mat4,
+    inverse: Mat4,
+    normal_transform: Mat4,
+}
+
+impl Placement {
+    fn new(transform: Mat4) -> Option<Self> {
+        let inverse = transform.inverse()?;
+        Some(Self {
+            transform,
+            inverse,
+            normal_transform: inverse.transpose(),
+        })
+    }
+
+    fn to_object(&self, ray: &Ray) -> Ray {
+        Ray::at_time(
+            self.inverse.transform_point(&ray.origin),
+            self.inverse.transform_vector(&ray.direction),
+            ray.time,
+        )
+    }
+
+    fn corners(&self, bbox: &Aabb) -> [Point; 8] {
+        std::array::from_fn(|corner| {
+            let pick = |bit: usize, axis: usize| {
+                if corner & bit == 0 {
+                    bbox.min[axis]
+                } else {
+                    bbox.max[axis]
+                }
+            };
+            self.transform
+                .transform_point(&Point::new(pick(1, 0), pick(2, 1), pick(4, 2)))
+        })
+    }
+}
+
+/// Instance of an object placed in the world by an affine transform. Rays are
+/// moved into object space, hits are moved back out with normals transformed
+/// by the inverse-transpose.
+#[derive(Debug)]
+pub struct Transformed {
+    object: Arc<dyn Hittable>,
+    /// The placement at time 0, used at all times when not animated
+    placement: Placement,
+    /// Steps at time 0 and time 1, interpolated in between and held outside
+    keyframes: Option<(Vec<TransformStep>, Vec<TransformStep>)>,
+    bbox: Aabb,
+}
+
+impl Transformed {
+    /// Times at which an animated instance is placed to bound its motion
+    const MOTION_SAMPLES: usize = 32;
+
+    /// `None` if `transform` is singular
+    pub fn new(object: Arc<dyn Hittable>, transform: Mat4) -> Option<Self> {
+        let placement = Placement::new(transform)?;
+        let object_box = object.bounding_box();
+        let bbox = if object_box.is_empty() {
+            object_box
+        } else {
+            placement
+                .corners(&object_box)
+                .iter()
+                .fold(Aabb::empty(), |bbox, corner| bbox.union_point(corner))
+        };
+        Some(Self {
+            object,
+            placement,
+            keyframes: None,
+            bbox,
+        })
+    }
+
+    /// Instance moving from the `start` steps at time 0 to the `end` steps at
+    /// time 1 by interpolating the parameters of each pair of steps. Panics if
+    /// the steps don't pair up by kind, `None` if the transform is singular at
+    /// either end.
+    pub fn animated(
+        object: Arc<dyn Hittable>,
+        start: Vec<TransformStep>,
+        end: Vec<TransformStep>,
+    ) -> Option<Self> {
+        assert!(
+            start.len() == end.len()
+                && start
+                    .iter()
+                    .zip(&end)
+                    .all(|(a, b)| a.lerp(b, 0.0).is_some()),
+            "animated instance needs the same kinds of steps at both ends"
+        );
+        let placement = Placement::new(TransformStep::compose(&start))?;
+        Placement::new(TransformStep::compose(&end))?;
+        let mut instance = Self {
+            object,
+            placement,
+            keyframes: Some((start, end)),
+            bbox: Aabb::empty(),
+        };
+
+        // Union of the boxes at evenly spaced times, grown by the furthest a
+        // corner moves between two of them so that the path in between is
+        // covered as well
+        let object_box = instance.object.bounding_box();
+        if !object_box.is_empty() {
+            let mut bbox = Aabb::empty();
+            let mut step = 0.0_f64;
+            let mut previous: Option<[Point; 8]> = None;
+            for i in 0..=Self::MOTION_SAMPLES {
+                let time = i as f64 / Self::MOTION_SAMPLES as f64;
+                let Some(placement) = instance.placement(time) else {
+                    continue;
+                };
+                let corners = placement.corners(&object_box);
+                bbox = corners
+                    .iter()
+                    .fold(bbox, |bbox, corner| bbox.union_point(corner));
+                if let Some(previous) = previous {
+                    for (a, b) in previous.iter().zip(&corners) {
+                        step = step.max((*a - *b).length());
+                    }
+                }
+                previous = Some(corners);
+            }
+            let pad = Vec3::new(step, step, step);
+            instance.bbox = Aabb::new(bbox.min - pad, bbox.max + pad);
+        }
+        Some(instance)
+    }
+
+    /// Where the object is at `time`, `None` if the transform is singular then
+    fn placement(&self, time: f64) -> Option<Cow<'_, Placement>> {
+        let Some((start, end)) = &self.keyframes else {
+            return Some(Cow::Borrowed(&self.placement));
+        };
+        let time = time.clamp(0.0, 1.0);
+        let transform = start
+            .iter()
+            .zip(end)
+            .filter_map(|(a, b)| a.lerp(b, time))
+            .fold(Mat4::identity(), |matrix, step| step.matrix() * matrix);
+        Placement::new(transform).map(Cow::Owned)
+    }
+}
+
+impl Hittable for Transformed {
+    /// The ray direction is transformed without normalizing, so the ray
+    /// parameter of a hit is the same in both spaces
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let placement = self.placement(ray.time)?;
+        let mut rec = self.object.hit(&placement.to_object(ray), t_min, t_max)?;
+        rec.point = placement.transform.transform_point(&rec.point);
+        rec.normal = placement
+            .normal_transform
+            .transform_vector(&rec.normal)
+            .unit_vec();
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        self.placement(ray.time).map_or(1.0, |placement| {
+            self.object
+                .transmittance(&placement.to_object(ray), t_min, t_max)
+        })
+    }
+
+    /// The object's density at the corresponding object space direction,
+    /// times the Jacobian of mapping world directions onto object directions
+    fn pdf_value(&self, origin: &Point, direction: &Vec3, time: f64) -> f64 {
+        let Some(placement) = self.placement(time) else {
+            return 0.0;
+        };
+        let local = placement.to_object(&Ray::at_time(*origin, *direction, time));
+        let pdf = self.object.pdf_value(&local.origin, &local.direction, time);
+        if pdf == 0.0 {
+            return 0.0;
+        }
+        let stretch = placement
+            .inverse
+            .transform_vector(&direction.unit_vec())
+            .length();
+        pdf * placement.inverse.linear_determinant().abs() / stretch.powi(3)
+    }
+
+    fn random(&self, origin: &Point, time: f64) -> Vec3 {
+        let Some(placement) = self.placement(time) else {
+            return Vec3::new(1.0, 0.0, 0.0);
+        };
+        let local_origin = placement.inverse.transform_point(origin);
+        placement
+            .transform
+            .transform_vector(&self.object.random(&local_origin, time))
+    }
+}
@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use crate::{
+    common::*,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+};
+
+pub type Uv = (f64, f64);
+
+/// Möller–Trumbore intersection, returning the ray parameter and the
+/// barycentric weights of the second and third vertices.
+pub(super) fn intersect(
+    p: &[Point; 3],
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64, f64)> {
+    let edge1 = p[1] - p[0];
+    let edge2 = p[2] - p[0];
+    let pvec = ray.direction.cross(&edge2);
+    let det = edge1.dot(&pvec);
+    // The determinant scales with the lengths of the direction and the edges,
+    // so it is compared relative to them to keep tiny triangles
+    let scale = ray.direction.length_squared() * edge1.length_squared() * edge2.length_squared();
+    if det * det <= 1e-24 * scale {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let tvec = ray.origin - p[0];
+    let b1 = tvec.dot(&pvec) * inv_det;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+    let qvec = tvec.cross(&edge1);
+    let b2 = ray.direction.dot(&qvec) * inv_det;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+
+    let time = edge2.dot(&qvec) * inv_det;
+    if time < t_min || t_max < time {
+        return None;
+    }
+    Some((time, b1, b2))
+}
+
+/// Builds the hit record for a triangle hit, interpolating shading normals and
+/// UVs when the triangle carries them. Without UVs the barycentric weights are used.
+pub(super) fn record(
+    p: &[Point; 3],
+    normals: Option<&[Vec3; 3]>,
+    uvs: Option<&[Uv; 3]>,
+    material: &Arc<dyn Material>,
+    ray: &Ray,
+    (time, b1, b2): (f64, f64, f64),
+) -> HitRecord {
+    let b0 = 1.0 - b1 - b2;
+    let outward_normal = (p[1] - p[0]).cross(&(p[2] - p[0])).unit_vec();
+    let mut rec = HitRecord::new(ray.at(time), time, material.clone(), ray, &outward_normal);
+
+    if let Some(n) = normals {
+        let shading = (b0 * n[0] + b1 * n[1] + b2 * n[2]).unit_vec();
+        rec.normal = if shading.dot(&rec.normal) < 0.0 {
+            -shading
+        } else {
+            shading
+        };
+    }
+    let (u, v) = match uvs {
+        Some(uv) => (
+            b0 * uv[0].0 + b1 * uv[1].0 + b2 * uv[2].0,
+            b0 * uv[0].1 + b1 * uv[1].1 + b2 * uv[2].1,
+        ),
+        None => (b1, b2),
+    };
+    rec.with_uv(u, v)
+}
+
+pub(super) fn bounding_box(p: &[Point; 3]) -> Aabb {
+    Aabb::new(p[0], p[1]).union_point(&p[2])
+}
+
+pub(super) fn area(p: &[Point; 3]) -> f64 {
+    0.5 * (p[1] - p[0]).cross(&(p[2] - p[0])).length()
+}
+
+/// Uniformly distributed point on the triangle
+pub(super) fn sample(p: &[Point; 3]) -> Point {
+    let r1 = random::unit().sqrt();
+    let r2 = random::unit();
+    (1.0 - r1) * p[0] + r1 * (1.0 - r2) * p[1] + r1 * r2 * p[2]
+}
+
+/// Solid angle density at `origin` of choosing `direction` towards the
+/// triangle when points are sampled with the area density `1 / area`
+pub(super) fn solid_angle_pdf(p: &[Point; 3], origin: &Point, direction: &Vec3, area: f64) -> f64 {
+    let ray = Ray::new(*origin, *direction);
+    let Some((time, _, _)) = intersect(p, &ray, 0.001, f64::INFINITY) else {
+        return 0.0;
+    };
+    let normal = (p[1] - p[0]).cross(&(p[2] - p[0])).unit_vec();
+    let length = direction.length();
+    let cosine = normal.dot(direction).abs() / length;
+    if cosine < 1e-8 {
+        return 0.0;
+    }
+    let distance = time * length;
+    distance * distance / (cosine * area)
+}
+
+#[derive(Debug)]
+pub struct Triangle {
+    pub vertices: [Point; 3],
+    pub normals: Option<[Vec3; 3]>,
+    pub uvs: Option<[Uv; 3]>,
+    pub material: Arc<dyn Material>,
+}
+
+impl Triangle {
+    pub fn new(a: Point, b: Point, c: Point, material: Arc<dyn Material>) -> Self {
+        Self {
+            vertices: [a, b, c],
+            normals: None,
+            uvs: None,
+            material,
+        }
+    }
+
+    pub fn with_normals(mut self, normals: [Vec3; 3]) -> Self {
+        self.normals = Some(normals);
+        self
+    }
+
+    pub fn with_uvs(mut self, uvs: [Uv; 3]) -> Self {
+        self.uvs = Some(uvs);
+        self
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let hit = intersect(&self.vertices, ray, t_min, t_max)?;
+        Some(record(
+            &self.vertices,
+            self.normals.as_ref(),
+            self.uvs.as_ref(),
+            &self.material,
+            ray,
+            hit,
+        ))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        bounding_box(&self.vertices)
+    }
+
+    fn pdf_value(&self, origin: &Point, direction: &Vec3, _time: f64) -> f64 {
+        solid_angle_pdf(&self.vertices, origin, direction, area(&self.vertices))
+    }
+
+    fn random(&self, origin: &Point, _time: f64) -> Vec3 {
+        sample(&self.vertices) - *origin
+    }
+}
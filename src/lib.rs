@@ -0,0 +1,25 @@
+//! A path tracer based on Ray Tracing in One Weekend
+//!
+//! Scenes are built in code ([`scene::builtin`]) or loaded from TOML
+//! ([`scene::description`]), rendered by a [`Renderer`] into a [`Film`] of
+//! linear radiance, and written out with [`output::OutputFormat`].
+
+pub mod camera;
+pub mod common;
+pub mod environment;
+pub mod film;
+pub mod hittable;
+pub mod light;
+pub mod material;
+pub mod obj;
+pub mod output;
+pub mod renderer;
+pub mod scene;
+pub mod texture;
+pub mod tile;
+pub mod tonemap;
+pub mod volume;
+
+pub use film::Film;
+pub use renderer::Renderer;
+pub use scene::{RenderSettings, Scene};
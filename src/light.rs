@@ -0,0 +1,23 @@
+pub mod directional;
+pub mod point;
+pub mod spot;
+
+use crate::common::*;
+
+/// Light arriving at a point from a single direction
+#[derive(Debug, Clone, Copy)]
+pub struct LightSample {
+    /// Unit vector from the point towards the light
+    pub direction: Vec3,
+    /// How far the light is, infinite for directional lights
+    pub distance: f64,
+    /// Irradiance on a surface facing the light
+    pub irradiance: Color,
+}
+
+/// Light from a single point or direction. Scattered rays can never find
+/// it, so it is only seen through shadow rays.
+pub trait Light: std::fmt::Debug + Send + Sync {
+    /// Light reaching `point` if nothing is in the way, `None` if none does
+    fn sample(&self, point: &Point) -> Option<LightSample>;
+}
@@ -0,0 +1,31 @@
+use crate::{
+    common::*,
+    light::{Light, LightSample},
+};
+
+/// Parallel light travelling along `direction` from infinitely far away,
+/// like sunlight, with `irradiance` on surfaces facing it
+#[derive(Debug, Clone)]
+pub struct DirectionalLight {
+    direction: Vec3,
+    pub irradiance: Color,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vec3, irradiance: Color) -> Self {
+        Self {
+            direction: direction.unit_vec(),
+            irradiance,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, _point: &Point) -> Option<LightSample> {
+        Some(LightSample {
+            direction: -self.direction,
+            distance: f64::INFINITY,
+            irradiance: self.irradiance,
+        })
+    }
+}
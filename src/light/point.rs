@@ -0,0 +1,37 @@
+use crate::{
+    common::*,
+    light::{Light, LightSample},
+};
+
+/// Shines `intensity` in all directions from `position`, falling off with
+/// the square of the distance
+#[derive(Debug, Clone)]
+pub struct PointLight {
+    pub position: Point,
+    pub intensity: Color,
+}
+
+impl PointLight {
+    pub fn new(position: Point, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, point: &Point) -> Option<LightSample> {
+        let offset = self.position - *point;
+        let distance_squared = offset.length_squared();
+        if distance_squared == 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+        Some(LightSample {
+            direction: offset / distance,
+            distance,
+            irradiance: self.intensity / distance_squared,
+        })
+    }
+}
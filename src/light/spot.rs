@@ -0,0 +1,67 @@
+use crate::{
+    common::*,
+    light::{Light, LightSample},
+};
+
+/// Point light shining only into a cone around `direction`. It has its full
+/// `intensity` up to `falloff_start` from the axis and fades out smoothly
+/// towards the edge of the cone at `cone_angle`.
+#[derive(Debug, Clone)]
+pub struct SpotLight {
+    pub position: Point,
+    direction: Vec3,
+    pub intensity: Color,
+    cos_cone: f64,
+    cos_falloff_start: f64,
+}
+
+impl SpotLight {
+    /// Angles are in degrees from the axis of the cone, with `falloff_start`
+    /// clamped to at most `cone_angle`
+    pub fn new(
+        position: Point,
+        direction: Vec3,
+        intensity: Color,
+        cone_angle: f64,
+        falloff_start: f64,
+    ) -> Self {
+        Self {
+            position,
+            direction: direction.unit_vec(),
+            intensity,
+            cos_cone: cone_angle.to_radians().cos(),
+            cos_falloff_start: falloff_start.min(cone_angle).to_radians().cos(),
+        }
+    }
+
+    /// Fraction of the intensity shone along the unit `direction`
+    fn falloff(&self, direction: &Vec3) -> f64 {
+        let cos = direction.dot(&self.direction);
+        if cos >= self.cos_falloff_start {
+            1.0
+        } else if cos <= self.cos_cone {
+            0.0
+        } else {
+            let t = (cos - self.cos_cone) / (self.cos_falloff_start - self.cos_cone);
+            t * t * (3.0 - 2.0 * t)
+        }
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, point: &Point) -> Option<LightSample> {
+        let offset = self.position - *point;
+        let distance_squared = offset.length_squared();
+        if distance_squared == 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+        let direction = offset / distance;
+        let falloff = self.falloff(&-direction);
+        (falloff > 0.0).then(|| LightSample {
+            direction,
+            distance,
+            irradiance: falloff * self.intensity / distance_squared,
+        })
+    }
+}
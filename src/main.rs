@@ -1,206 +1,273 @@
-mod camera;
-mod common;
-mod hittable;
-mod material;
-
-use common::*;
-use hittable::Hittable;
-use hittable::{hit_list::HitList, sphere::Sphere};
-use material::Material;
-use material::{dielectric::Dielectric, lambertian::Lambertian, metal::Metal};
-use rand::Rng;
-use rand_distr::Pareto;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::thread;
-use std::time::Duration;
-use std::{
-    fs::File,
-    io::{stdout, BufWriter, Write},
-    sync::Arc,
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use pathtracer::{
+    common::random,
+    output::{exr::ExrPrecision, OutputFormat},
+    scene::{
+        builtin::Builtin,
+        description::{SceneDescription, SceneError},
+    },
+    tile::TileOrder,
+    tonemap::{ToneMapper, ToneMapping},
+    RenderSettings, Renderer, Scene,
 };
-
-use crate::camera::Camera;
+use std::io::{stdout, Write};
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::path::PathBuf;
+use std::process;
 
 const RESET_LINE: &str = "\x1B[2K\r";
 
-fn ray_color(ray: &Ray, world: &HitList, depth: usize) -> Color {
-    let black = Color::new(0.0, 0.0, 0.0);
-    if depth == 0 {
-        return black;
+/// Renders a built-in scene or a TOML scene file to an image
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    /// Output image path; the format follows the extension (png, ppm, exr, hdr or pfm)
+    #[arg(short, long, default_value = "image.png")]
+    output: PathBuf,
+
+    /// Write 32-bit float instead of half float channels to EXR files
+    #[arg(long)]
+    exr_float: bool,
+
+    /// Tone mapping operator for 8-bit output (clamp, reinhard, extended-reinhard, aces or hable)
+    #[arg(long, default_value_t = ToneMapper::Clamp, value_parser = clap::value_parser!(ToneMapper))]
+    tonemap: ToneMapper,
+
+    /// Exposure compensation in stops, applied before tone mapping
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true, value_parser = parse_exposure)]
+    exposure: f64,
+
+    /// Luminance mapped to white by the extended Reinhard operator
+    #[arg(long, value_parser = parse_white_point)]
+    white_point: Option<f64>,
+
+    /// Scene description file (TOML)
+    #[arg(long, conflicts_with = "builtin")]
+    scene: Option<PathBuf>,
+
+    /// Built-in scene to render when no scene file is given
+    #[arg(long, default_value_t = Builtin::Random, value_parser = clap::value_parser!(Builtin))]
+    builtin: Builtin,
+
+    /// Image width in pixels; keeps the scene's aspect ratio if height is not given
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    width: Option<u32>,
+
+    /// Image height in pixels; keeps the scene's aspect ratio if width is not given
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    height: Option<u32>,
+
+    /// Samples per pixel
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..))]
+    spp: Option<u32>,
+
+    /// Maximum number of bounces per path
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    max_depth: Option<u32>,
+
+    /// Bounces before Russian roulette may end a path
+    #[arg(long)]
+    min_bounces: Option<u32>,
+
+    /// Worker threads [default: available parallelism]
+    #[arg(short = 'j', long, value_parser = clap::value_parser!(u32).range(1..))]
+    threads: Option<u32>,
+
+    /// Edge length of the square tiles handed out to worker threads
+    #[arg(long, default_value_t = 32, value_parser = parse_tile_size)]
+    tile_size: usize,
+
+    /// Order in which tiles are rendered (scanline, spiral or hilbert)
+    #[arg(long, default_value_t = TileOrder::Spiral, value_parser = clap::value_parser!(TileOrder))]
+    tile_order: TileOrder,
+
+    /// Seed for scene generation and rendering [default: random, printed unless quiet]
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Don't print progress
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+fn parse_white_point(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(value) if value > 0.0 && value.is_finite() => Ok(value),
+        Ok(_) => Err("white point must be positive".into()),
+        Err(err) => Err(err.to_string()),
     }
-    let white = Color::new(1.0, 1.0, 1.0);
-    if let Some(hit_rec) = world.hit(ray, 0.001, f64::INFINITY) {
-        if let Some(mat_rec) = hit_rec.material.scatter(ray, &hit_rec) {
-            mat_rec.attenuation * ray_color(&mat_rec.scattered, world, depth - 1)
-            // let target = hit_rec.point + hit_rec.normal + Vec3::random_unit_vector();
-            // let target = rec.point + Vec3::random_in_hemisphere(&rec.normal);
-            // 0.5 * ray_color(&Ray::new(hit_rec.point, target - hit_rec.point), world, depth - 1)
-        } else {
-            black
-        }
-    } else {
-        let unit_dir = ray.direction.unit_vec();
-        let alpha = 0.5 * (unit_dir.y() + 1.0);
-        (1.0 - alpha) * white + alpha * Color::new(0.5, 0.7, 1.0)
+}
+
+fn parse_exposure(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(value),
+        Ok(_) => Err("exposure must be finite".into()),
+        Err(err) => Err(err.to_string()),
     }
 }
 
-fn write_color(f: &mut impl Write, color: &Color, samples_per_pixel: u32) {
-    fn translate_rgb_to_int(value: f64) -> i64 {
-        (value.clamp(0.0, 0.999) * 256.0) as i64
+fn parse_tile_size(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(0) => Err("tile size must be positive".into()),
+        Ok(size) => Ok(size),
+        Err(err) => Err(err.to_string()),
     }
-    let mut r = color.0;
-    let mut g = color.1;
-    let mut b = color.2;
-
-    let scale = 1.0 / samples_per_pixel as f64;
-    r = (r * scale).sqrt();
-    g = (g * scale).sqrt();
-    b = (b * scale).sqrt();
-
-    writeln!(
-        f,
-        "{} {} {}",
-        translate_rgb_to_int(r),
-        translate_rgb_to_int(g),
-        translate_rgb_to_int(b)
-    )
-    .unwrap()
 }
 
-fn make_random_scene() -> HitList {
-    let mut world = HitList::default();
-    let ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
-    world.add(Arc::new(Sphere::new(
-        Point::new(0.0, -1000.0, 0.0),
-        1000.0,
-        ground,
-    )));
-    for a in -11..11 {
-        for b in -11..11 {
-            let material_choice = random::unit();
-            let size: f64 = rand::thread_rng().sample(Pareto::new(1., 2.).unwrap()) * 0.07;
-            let size = size.min(0.6);
-            let center = Point::new(
-                a as f64 + 0.9 * random::unit(),
-                size,
-                b as f64 + 0.9 * random::unit(),
-            );
-            if (center - Point::new(4.0, size, 0.0)).length() > 0.9 {
-                let mat: Arc<dyn Material> = if material_choice < 0.8 {
-                    let albedo = Color::random_unit() * Color::random_unit();
-                    Arc::new(Lambertian::new(albedo))
-                } else if material_choice < 0.95 {
-                    let albedo = Color::random(0.5, 1.0);
-                    let fuzz = random::range(0.0, 0.5);
-                    Arc::new(Metal::new(albedo, fuzz))
-                } else {
-                    Arc::new(Dielectric::new(1.5))
-                };
-                world.add(Arc::new(Sphere::new(center, size, mat)));
+impl Args {
+    fn tone_mapping(&self) -> ToneMapping {
+        let mut operator = self.tonemap;
+        if let (ToneMapper::ExtendedReinhard { white_point }, Some(value)) =
+            (&mut operator, self.white_point)
+        {
+            *white_point = value;
+        }
+        ToneMapping {
+            operator,
+            exposure: self.exposure,
+        }
+    }
+
+    fn apply(&self, settings: &mut RenderSettings) {
+        let aspect_ratio = settings.aspect_ratio();
+        match (self.width, self.height) {
+            (Some(width), Some(height)) => {
+                settings.image_width = width as usize;
+                settings.image_height = height as usize;
+            }
+            (Some(width), None) => {
+                settings.image_width = width as usize;
+                settings.image_height = ((width as f64 / aspect_ratio).round() as usize).max(1);
+            }
+            (None, Some(height)) => {
+                settings.image_height = height as usize;
+                settings.image_width = ((height as f64 * aspect_ratio).round() as usize).max(1);
+            }
+            (None, None) => {}
+        }
+        if let Some(spp) = self.spp {
+            settings.samples_per_pixel = spp;
+        }
+        if let Some(max_depth) = self.max_depth {
+            settings.max_depth = max_depth as usize;
+        }
+        if let Some(min_bounces) = self.min_bounces {
+            settings.min_bounces = min_bounces as usize;
+        }
+    }
+
+    fn load_scene(&self) -> Result<Scene, SceneError> {
+        match &self.scene {
+            Some(path) => {
+                let mut description = SceneDescription::load(path)?;
+                let mut settings = description.settings();
+                self.apply(&mut settings);
+                description.image.width = NonZeroUsize::new(settings.image_width).unwrap();
+                description.image.height = NonZeroUsize::new(settings.image_height).unwrap();
+                description.render.samples_per_pixel =
+                    NonZeroU32::new(settings.samples_per_pixel).unwrap();
+                description.render.max_depth = settings.max_depth;
+                description.render.min_bounces = settings.min_bounces;
+                description.build()
+            }
+            None => {
+                let mut settings = self.builtin.settings();
+                self.apply(&mut settings);
+                Ok(self.builtin.build(settings))
             }
         }
     }
-    world.add(Arc::new(Sphere::new(
-        Point::new(0.0, 1.0, 0.0),
-        1.0,
-        Arc::new(Dielectric::new(1.5)),
-    )));
-    world.add(Arc::new(Sphere::new(
-        Point::new(-4.0, 1.0, 0.0),
-        1.0,
-        Arc::new(Lambertian::new(Color::new(0.4, 0.2, 0.1))),
-    )));
-    world.add(Arc::new(Sphere::new(
-        Point::new(4.0, 1.0, 0.0),
-        1.0,
-        Arc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0)),
-    )));
-    world
 }
 
 fn main() {
-    let output = File::create("image.ppm").unwrap();
-    let mut out = BufWriter::new(output);
-
-    // Image
-    let aspect_ratio = 3.0 / 2.0;
-    let image_width = 1200.0;
-    let image_height = image_width / aspect_ratio;
-    let samples_per_pixel = 10;
-    let max_depth = 50;
-
-    writeln!(out, "P3").unwrap();
-    writeln!(out, "{image_width} {image_height}").unwrap();
-    writeln!(out, "255").unwrap();
-
-    // World
-    let world = make_random_scene();
-
-    // Camera
-    let lookfrom = Point::new(13.0, 2.0, 3.0);
-    let lookat = Point::new(0.0, 0.0, 0.0);
-    let vup = Vec3::new(0.0, 1.0, 0.0);
-    let dist_to_focus = 10.0;
-    let aperture = 0.1;
-    let camera = Camera::new(
-        lookfrom,
-        lookat,
-        vup,
-        20.0,
-        aspect_ratio,
-        aperture,
-        dist_to_focus,
-    );
-    let worker_count = 6;
-
-    let counter = AtomicUsize::new(0);
-    std::thread::scope(|s| {
-        let mut workers = vec![];
-        let mut i = image_height as i64;
-        let step = image_height as i64 / worker_count + 1;
-        while i > 0 {
-            let worker_start = i;
-            i -= step;
-            let worker_stop = i.max(0);
-            let world = &world;
-            let camera = &camera;
-            let counter = &counter;
-            workers.push(s.spawn(move || {
-                let mut res = vec![];
-                for iy in (worker_stop..worker_start).rev() {
-                    for ix in 0..image_width as i64 {
-                        let mut pixel_color = Color::new(0.0, 0.0, 0.0);
-                        for _ in 0..samples_per_pixel {
-                            let u = (ix as f64 + random::unit()) / (image_width - 1.0);
-                            let v = (iy as f64 + random::unit()) / (image_height - 1.0);
-                            let ray = camera.get_ray(u, v);
-                            pixel_color += ray_color(&ray, world, max_depth);
-                        }
-                        res.push(pixel_color);
-                    }
-                    counter.fetch_add(1, Ordering::Relaxed);
-                }
-                res
-            }));
+    let args = Args::parse();
+    if args.white_point.is_some() && !matches!(args.tonemap, ToneMapper::ExtendedReinhard { .. }) {
+        Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--white-point only applies to --tonemap extended-reinhard",
+            )
+            .exit();
+    }
+    if args.exr_float
+        && !matches!(
+            OutputFormat::from_path(&args.output),
+            Some(OutputFormat::Exr(_))
+        )
+    {
+        Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--exr-float only applies to EXR output",
+            )
+            .exit();
+    }
+    // Scene generation and rendering share the seed so a printed seed
+    // reproduces the whole image
+    let seed = args.seed.unwrap_or_else(random::entropy_seed);
+    if !args.quiet && args.seed.is_none() {
+        println!("Seed: {seed}");
+    }
+    random::seed(seed);
+    let scene = match args.load_scene() {
+        Ok(scene) => scene,
+        Err(err) => {
+            eprintln!("error: {err}");
+            process::exit(1);
         }
-        s.spawn(|| loop {
-            let count = counter.load(Ordering::Relaxed);
-            print!("{RESET_LINE}");
-            if count >= image_height as usize {
-                println!("Done.");
-                break;
-            } else {
-                print!("Progress: {count} / {image_height}");
-                stdout().flush().unwrap();
-                thread::sleep(Duration::from_secs(1));
-            }
+    };
+    let Some(mut format) = OutputFormat::from_path(&args.output) else {
+        eprintln!(
+            "error: unsupported output format for {}, expected one of: {}",
+            args.output.display(),
+            OutputFormat::EXTENSIONS.join(", ")
+        );
+        process::exit(1);
+    };
+    if args.exr_float {
+        format = OutputFormat::Exr(ExrPrecision::Float);
+    }
+
+    let renderer = Renderer {
+        threads: match args.threads {
+            Some(threads) => threads as usize,
+            None => Renderer::default().threads,
+        },
+        tile_size: args.tile_size,
+        tile_order: args.tile_order,
+        seed: Some(seed),
+    };
+    let output = if args.quiet {
+        renderer.render(&scene)
+    } else {
+        let output = renderer.render_with_progress(&scene, |done, total| {
+            let mut stdout = stdout().lock();
+            write!(stdout, "{RESET_LINE}Progress: {done} / {total} tiles").unwrap();
+            stdout.flush().unwrap();
         });
-        for worker in workers {
-            let res = worker.join().unwrap();
-            for color in res {
-                write_color(&mut out, &color, samples_per_pixel);
-            }
-        }
-    })
+        println!("{RESET_LINE}Done.");
+        let stats = &output.stats;
+        let percent = |count: u64| 100.0 * count as f64 / stats.paths.max(1) as f64;
+        println!(
+            "Paths: {}, mean bounces: {:.2}, mean final throughput: {:.3}",
+            stats.paths,
+            stats.mean_bounces(),
+            stats.mean_final_throughput()
+        );
+        println!(
+            "Ended by: escaping {:.1}%, absorption {:.1}%, roulette {:.1}%, max depth {:.1}%",
+            percent(stats.escaped),
+            percent(stats.absorbed),
+            percent(stats.roulette_terminated),
+            percent(stats.max_depth_reached)
+        );
+        output
+    };
+    let film = output.film;
+
+    let tonemap = args.tone_mapping();
+    if let Err(err) = format.save(&args.output, &film, |film| film.develop(&tonemap)) {
+        eprintln!("error: cannot write {}: {err}", args.output.display());
+        process::exit(1);
+    }
 }
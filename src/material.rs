@@ -1,4 +1,7 @@
+pub mod conductor;
 pub mod dielectric;
+pub mod diffuse_light;
+pub mod henyey_greenstein;
 pub mod lambertian;
 pub mod metal;
 
@@ -6,9 +9,55 @@ use crate::{common::*, hittable::HitRecord};
 
 pub struct ScatterRecord {
     pub scattered: Ray,
+    /// `eval / pdf` for the scattered direction, or the reflectance of a
+    /// specular lobe
     pub attenuation: Color,
+    /// Solid angle density of the scattered direction, zero for specular lobes
+    pub pdf: f64,
+    /// The direction came from a delta lobe that `eval` and `pdf` don't cover
+    pub is_specular: bool,
+}
+
+impl ScatterRecord {
+    pub fn specular(scattered: Ray, attenuation: Color) -> Self {
+        Self {
+            scattered,
+            attenuation,
+            pdf: 0.0,
+            is_specular: true,
+        }
+    }
 }
 
 pub trait Material: std::fmt::Debug + Send + Sync {
     fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord>;
+
+    /// Radiance emitted from the hit point back along `ray_in`
+    fn emitted(&self, _ray_in: &Ray, _rec: &HitRecord) -> Color {
+        Color::black()
+    }
+
+    /// BSDF times the cosine term for light arriving from `wi` and leaving
+    /// towards `wo`, both unit vectors pointing away from the surface.
+    /// Specular lobes are not included.
+    fn eval(&self, _rec: &HitRecord, _wi: &Vec3, _wo: &Vec3) -> Color {
+        Color::black()
+    }
+
+    /// Solid angle density with which `scatter` picks `wi` when looking from `wo`
+    fn pdf(&self, _rec: &HitRecord, _wi: &Vec3, _wo: &Vec3) -> f64 {
+        0.0
+    }
+
+    /// Whether `eval` can be non-black, so lights are worth sampling at hits on
+    /// this material
+    fn has_non_specular_lobe(&self) -> bool {
+        false
+    }
+
+    /// Whether `emitted` can be non-black, so objects made of this material
+    /// should be sampled as lights
+    fn is_emissive(&self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::*,
+    hittable::HitRecord,
+    material::{Material, ScatterRecord},
+};
+
+/// Below this the microfacets are treated as a perfect mirror
+const MIN_ALPHA: f64 = 1e-3;
+
+/// Measured metals, with the complex index of refraction at wavelengths
+/// standing in for the red, green and blue channels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    Gold,
+    Copper,
+    Aluminium,
+    Silver,
+}
+
+impl Preset {
+    /// Real part `eta` and extinction coefficient `k`
+    pub fn ior(&self) -> (Color, Color) {
+        match self {
+            Preset::Gold => (
+                Color::new(0.143, 0.374, 1.442),
+                Color::new(3.983, 2.385, 1.603),
+            ),
+            Preset::Copper => (
+                Color::new(0.200, 0.924, 1.102),
+                Color::new(3.912, 2.452, 2.142),
+            ),
+            Preset::Aluminium => (
+                Color::new(1.657, 0.880, 0.521),
+                Color::new(9.224, 6.270, 4.837),
+            ),
+            Preset::Silver => (
+                Color::new(0.155, 0.117, 0.138),
+                Color::new(4.828, 3.122, 2.147),
+            ),
+        }
+    }
+}
+
+/// Unpolarized reflectance of a conductor with index of refraction
+/// `eta + i k` relative to the outside, for light arriving at `cos_i` to the
+/// normal
+fn fresnel(cos_i: f64, eta: f64, k: f64) -> f64 {
+    let cos2 = cos_i.clamp(0.0, 1.0).powi(2);
+    let sin2 = 1.0 - cos2;
+    let (eta2, k2) = (eta * eta, k * k);
+    let t0 = eta2 - k2 - sin2;
+    let a2b2 = (t0 * t0 + 4.0 * eta2 * k2).sqrt();
+    let t1 = a2b2 + cos2;
+    let t2 = 2.0 * cos2.sqrt() * (0.5 * (a2b2 + t0)).max(0.0).sqrt();
+    let rs = (t1 - t2) / (t1 + t2);
+    let t3 = cos2 * a2b2 + sin2 * sin2;
+    let t4 = t2 * sin2;
+    let rp = rs * (t3 - t4) / (t3 + t4);
+    0.5 * (rs + rp)
+}
+
+/// Rough metal made of microscopic mirrors whose normals follow the GGX
+/// distribution, with Smith masking-shadowing between them. The roughness
+/// may differ along a tangent direction and across it, like on brushed metal.
+/// Reflections are sampled from the normals visible from the viewer, so
+/// `attenuation` stays close to the Fresnel reflectance.
+#[derive(Debug, Clone)]
+pub struct Conductor {
+    eta: Color,
+    k: Color,
+    alpha_u: f64,
+    alpha_v: f64,
+    /// World-space direction that `alpha_u` applies along once projected
+    /// onto the surface
+    tangent: Vec3,
+}
+
+impl Conductor {
+    /// Roughnesses between 0 for a mirror and 1 for a very rough surface,
+    /// squared to get the GGX widths so that they look about linear.
+    /// `roughness_u` applies along the x axis unless set by `with_tangent`.
+    pub fn new(eta: Color, k: Color, roughness_u: f64, roughness_v: f64) -> Self {
+        Self {
+            eta,
+            k,
+            alpha_u: roughness_u.clamp(0.0, 1.0).powi(2),
+            alpha_v: roughness_v.clamp(0.0, 1.0).powi(2),
+            tangent: Vec3::new(1.0, 0.0, 0.0),
+        }
+    }
+
+    pub fn preset(preset: Preset, roughness_u: f64, roughness_v: f64) -> Self {
+        let (eta, k) = preset.ior();
+        Self::new(eta, k, roughness_u, roughness_v)
+    }
+
+    /// Orients the roughness: `roughness_u` applies along `tangent`
+    /// projected onto the surface. Where the normal is parallel to it the
+    /// orientation is arbitrary.
+    pub fn with_tangent(mut self, tangent: Vec3) -> Self {
+        self.tangent = tangent;
+        self
+    }
+
+    fn is_mirror(&self) -> bool {
+        self.alpha_u < MIN_ALPHA && self.alpha_v < MIN_ALPHA
+    }
+
+    fn alphas(&self) -> (f64, f64) {
+        (self.alpha_u.max(MIN_ALPHA), self.alpha_v.max(MIN_ALPHA))
+    }
+
+    fn reflectance(&self, cos_i: f64) -> Color {
+        Color::new(
+            fresnel(cos_i, self.eta.x(), self.k.x()),
+            fresnel(cos_i, self.eta.y(), self.k.y()),
+            fresnel(cos_i, self.eta.z(), self.k.z()),
+        )
+    }
+
+    /// Density of microfacet normals `h` in the local frame, with z along
+    /// the normal
+    fn distribution(&self, h: &Vec3) -> f64 {
+        let (ax, ay) = self.alphas();
+        let d = (h.x() / ax).powi(2) + (h.y() / ay).powi(2) + h.z() * h.z();
+        1.0 / (PI * ax * ay * d * d)
+    }
+
+    /// Smith's auxiliary function for the microfacets hidden looking along
+    /// the local direction `w`
+    fn lambda(&self, w: &Vec3) -> f64 {
+        let (ax, ay) = self.alphas();
+        let tan2 = ((w.x() * ax).powi(2) + (w.y() * ay).powi(2)) / (w.z() * w.z());
+        0.5 * ((1.0 + tan2).sqrt() - 1.0)
+    }
+
+    /// Microfacet normal visible from the local direction `wo`, by
+    /// stretching the distribution into a hemisphere and sampling its
+    /// projection (Heitz 2018)
+    fn sample_visible_normal(&self, wo: &Vec3) -> Vec3 {
+        let (ax, ay) = self.alphas();
+        let v = Vec3::new(ax * wo.x(), ay * wo.y(), wo.z()).unit_vec();
+        let length_squared = v.x() * v.x() + v.y() * v.y();
+        let t1 = if length_squared > 0.0 {
+            Vec3::new(-v.y(), v.x(), 0.0) / length_squared.sqrt()
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let t2 = v.cross(&t1);
+        let r = random::unit().sqrt();
+        let phi = 2.0 * PI * random::unit();
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + v.z());
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * r * phi.sin();
+        let n = p1 * t1 + p2 * t2 + (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt() * v;
+        Vec3::new(ax * n.x(), ay * n.y(), n.z().max(0.0)).unit_vec()
+    }
+
+    /// Basis around the normal with `u` along the tangent
+    fn basis(&self, normal: &Vec3) -> Onb {
+        Onb::with_tangent(normal, &self.tangent).unwrap_or_else(|| Onb::new(normal))
+    }
+
+    /// World directions in the basis around the normal
+    fn to_local(basis: &Onb, w: &Vec3) -> Vec3 {
+        Vec3::new(w.dot(&basis.u), w.dot(&basis.v), w.dot(&basis.w))
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        let wo = -ray_in.direction.unit_vec();
+        if self.is_mirror() {
+            let reflected = (-wo).reflect(&rec.normal);
+            let cos = rec.normal.dot(&wo);
+            return (cos > 0.0).then(|| {
+                ScatterRecord::specular(
+                    Ray::at_time(rec.point, reflected, ray_in.time),
+                    self.reflectance(cos),
+                )
+            });
+        }
+        let basis = self.basis(&rec.normal);
+        let local_wo = Self::to_local(&basis, &wo);
+        if local_wo.z() <= 0.0 {
+            return None;
+        }
+        let h = self.sample_visible_normal(&local_wo);
+        let local_wi = (-local_wo).reflect(&h);
+        if local_wi.z() <= 0.0 {
+            return None;
+        }
+        // eval / pdf reduces to the Fresnel term times the masking of the
+        // light given that the view is unmasked
+        let (lambda_o, lambda_i) = (self.lambda(&local_wo), self.lambda(&local_wi));
+        let masking = (1.0 + lambda_o) / (1.0 + lambda_o + lambda_i);
+        let wi = basis.local(&local_wi);
+        Some(ScatterRecord {
+            scattered: Ray::at_time(rec.point, wi, ray_in.time),
+            attenuation: masking * self.reflectance(local_wo.dot(&h)),
+            pdf: self.pdf(rec, &wi, &wo),
+            is_specular: false,
+        })
+    }
+
+    /// `D G F / (4 cos θo)`, the BRDF with one of its cosines cancelled by
+    /// the cosine term
+    fn eval(&self, rec: &HitRecord, wi: &Vec3, wo: &Vec3) -> Color {
+        if self.is_mirror() {
+            return Color::black();
+        }
+        let basis = self.basis(&rec.normal);
+        let (wi, wo) = (Self::to_local(&basis, wi), Self::to_local(&basis, wo));
+        if wi.z() <= 0.0 || wo.z() <= 0.0 {
+            return Color::black();
+        }
+        let h = (wi + wo).unit_vec();
+        let masking = 1.0 / (1.0 + self.lambda(&wo) + self.lambda(&wi));
+        self.distribution(&h) * masking / (4.0 * wo.z()) * self.reflectance(wo.dot(&h))
+    }
+
+    /// The density of visible normals, `G1(wo) D(h) / (4 cos θo)` after the
+    /// Jacobian of reflecting about `h`
+    fn pdf(&self, rec: &HitRecord, wi: &Vec3, wo: &Vec3) -> f64 {
+        if self.is_mirror() {
+            return 0.0;
+        }
+        let basis = self.basis(&rec.normal);
+        let (wi, wo) = (Self::to_local(&basis, wi), Self::to_local(&basis, wo));
+        if wi.z() <= 0.0 || wo.z() <= 0.0 {
+            return 0.0;
+        }
+        let h = (wi + wo).unit_vec();
+        self.distribution(&h) / (1.0 + self.lambda(&wo)) / (4.0 * wo.z())
+    }
+
+    fn has_non_specular_lobe(&self) -> bool {
+        !self.is_mirror()
+    }
+}
@@ -41,9 +41,9 @@ impl Material for Dielectric {
                 unit_direction.refract(&rec.normal, refraction_ratio)
             };
 
-        Some(ScatterRecord {
-            scattered: Ray::new(rec.point, direction),
+        Some(ScatterRecord::specular(
+            Ray::at_time(rec.point, direction, ray_in.time),
             attenuation,
-        })
+        ))
     }
 }
@@ -0,0 +1,47 @@
+use crate::{
+    common::*,
+    hittable::HitRecord,
+    material::{Material, ScatterRecord},
+};
+
+/// Area light that emits uniformly in all directions from its front face,
+/// or from both faces if `two_sided` is set
+#[derive(Debug)]
+pub struct DiffuseLight {
+    pub emit: Color,
+    pub two_sided: bool,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        Self {
+            emit,
+            two_sided: false,
+        }
+    }
+
+    pub fn two_sided(emit: Color) -> Self {
+        Self {
+            emit,
+            two_sided: true,
+        }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _ray_in: &Ray, _rec: &HitRecord) -> Option<ScatterRecord> {
+        None
+    }
+
+    fn emitted(&self, _ray_in: &Ray, rec: &HitRecord) -> Color {
+        if rec.front_face || self.two_sided {
+            self.emit
+        } else {
+            Color::black()
+        }
+    }
+
+    fn is_emissive(&self) -> bool {
+        true
+    }
+}
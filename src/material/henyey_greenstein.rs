@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use crate::{
+    common::*,
+    hittable::HitRecord,
+    material::{Material, ScatterRecord},
+    texture::{solid::SolidColor, Texture},
+};
+
+/// Phase function of a participating medium favouring forward scattering for
+/// positive `g` and backward scattering for negative `g`; zero is isotropic
+#[derive(Debug)]
+pub struct HenyeyGreenstein {
+    pub albedo: Arc<dyn Texture>,
+    /// Mean cosine of the scattering angle, between -1 and 1 exclusive
+    pub g: f64,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(albedo: Color, g: f64) -> Self {
+        Self::textured(Arc::new(SolidColor::new(albedo)), g)
+    }
+
+    pub fn textured(albedo: Arc<dyn Texture>, g: f64) -> Self {
+        Self { albedo, g }
+    }
+
+    /// Scatters equally in all directions
+    pub fn isotropic(albedo: Color) -> Self {
+        Self::new(albedo, 0.0)
+    }
+
+    /// Density of turning by an angle with cosine `cos_theta` away from the
+    /// direction of travel
+    fn phase(&self, cos_theta: f64) -> f64 {
+        let g = self.g;
+        let denominator = 1.0 + g * g - 2.0 * g * cos_theta;
+        (1.0 - g * g) / (4.0 * PI * denominator * denominator.sqrt())
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        let g = self.g;
+        let (direction, cos_theta) = if g.abs() < 1e-3 {
+            // Close enough to isotropic to sample the sphere uniformly
+            let direction = Vec3::random_unit_vector();
+            (direction, direction.dot(&ray_in.direction.unit_vec()))
+        } else {
+            // Inverts the cumulative distribution of the phase function
+            let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * random::unit());
+            let cos_theta = ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0);
+            let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+            let phi = 2.0 * PI * random::unit();
+            let local = Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
+            (Onb::new(&ray_in.direction).local(&local), cos_theta)
+        };
+        Some(ScatterRecord {
+            scattered: Ray::at_time(rec.point, direction, ray_in.time),
+            attenuation: self.albedo.value(rec.u, rec.v, &rec.point),
+            pdf: self.phase(cos_theta),
+            is_specular: false,
+        })
+    }
+
+    /// There is no cosine term inside a medium
+    fn eval(&self, rec: &HitRecord, wi: &Vec3, wo: &Vec3) -> Color {
+        self.albedo.value(rec.u, rec.v, &rec.point) * self.pdf(rec, wi, wo)
+    }
+
+    /// The scattering angle is the one between `-wo`, the direction the path
+    /// arrived in, and `wi`
+    fn pdf(&self, _rec: &HitRecord, wi: &Vec3, wo: &Vec3) -> f64 {
+        self.phase(-wo.dot(wi))
+    }
+
+    fn has_non_specular_lobe(&self) -> bool {
+        true
+    }
+}
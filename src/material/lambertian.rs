@@ -1,29 +1,50 @@
+use std::sync::Arc;
+
 use crate::{
     common::*,
     hittable::HitRecord,
     material::{Material, ScatterRecord},
+    texture::{solid::SolidColor, Texture},
 };
 
 #[derive(Debug)]
 pub struct Lambertian {
-    pub albedo: Color,
+    pub albedo: Arc<dyn Texture>,
 }
 
 impl Lambertian {
     pub fn new(albedo: Color) -> Self {
+        Self::textured(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn textured(albedo: Arc<dyn Texture>) -> Self {
         Self { albedo }
     }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _ray_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
-        let mut scatter_direction = rec.normal + Vec3::random_unit_vector();
-        if scatter_direction.near_zero() {
-            scatter_direction = rec.normal;
-        }
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        let direction = Onb::new(&rec.normal).local(&Vec3::random_cosine_direction());
+        let cosine = rec.normal.dot(&direction).max(0.0);
+        // The cosine density cancels against the BRDF's cosine term
         Some(ScatterRecord {
-            scattered: Ray::new(rec.point, scatter_direction),
-            attenuation: self.albedo,
+            scattered: Ray::at_time(rec.point, direction, ray_in.time),
+            attenuation: self.albedo.value(rec.u, rec.v, &rec.point),
+            pdf: cosine / PI,
+            is_specular: false,
         })
     }
+
+    /// albedo / pi times the cosine term, which is the albedo times the pdf
+    fn eval(&self, rec: &HitRecord, wi: &Vec3, wo: &Vec3) -> Color {
+        self.albedo.value(rec.u, rec.v, &rec.point) * self.pdf(rec, wi, wo)
+    }
+
+    fn pdf(&self, rec: &HitRecord, wi: &Vec3, _wo: &Vec3) -> f64 {
+        rec.normal.dot(wi).max(0.0) / PI
+    }
+
+    fn has_non_specular_lobe(&self) -> bool {
+        true
+    }
 }
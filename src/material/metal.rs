@@ -1,35 +1,84 @@
+use std::sync::Arc;
+
 use crate::{
     common::*,
+    hittable::HitRecord,
     material::{Material, ScatterRecord},
+    texture::{solid::SolidColor, Texture},
 };
 
+/// Mirror reflection perturbed by a random offset within a ball of radius
+/// `fuzz`. The BRDF times cosine is defined to be the albedo times the density
+/// of that offset, so directions below the surface are absorbed.
 #[derive(Debug)]
 pub struct Metal {
-    albedo: Color,
+    albedo: Arc<dyn Texture>,
     fuzz: f64,
 }
 
 impl Metal {
     pub fn new(albedo: Color, fuzz: f64) -> Self {
+        Self::textured(Arc::new(SolidColor::new(albedo)), fuzz)
+    }
+
+    pub fn textured(albedo: Arc<dyn Texture>, fuzz: f64) -> Self {
         let fuzz = fuzz.min(1.0);
         Self { albedo, fuzz }
     }
 }
 
+impl Metal {
+    /// Density over directions of `reflected + fuzz * p` with `p` uniform in
+    /// the unit ball: the volume of the ball along the ray through `wi` over
+    /// the ball's volume
+    fn fuzz_pdf(&self, reflected: &Vec3, wi: &Vec3) -> f64 {
+        let b = wi.dot(reflected);
+        let discriminant = b * b - (1.0 - self.fuzz * self.fuzz);
+        if b <= 0.0 || discriminant <= 0.0 {
+            return 0.0;
+        }
+        let t0 = b - discriminant.sqrt();
+        let t1 = b + discriminant.sqrt();
+        (t1.powi(3) - t0.powi(3)) / (4.0 * PI * self.fuzz.powi(3))
+    }
+}
+
 impl Material for Metal {
-    fn scatter(&self, ray_in: &Ray, rec: &crate::hittable::HitRecord) -> Option<ScatterRecord> {
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
         let reflected = ray_in.direction.unit_vec().reflect(&rec.normal);
-        let scattered = Ray::new(
-            rec.point,
-            reflected + self.fuzz * Vec3::random_in_unit_sphere(),
-        );
-        if scattered.direction.dot(&rec.normal) > 0.0 {
-            Some(ScatterRecord {
-                scattered,
-                attenuation: self.albedo,
-            })
-        } else {
-            None
+        let attenuation = self.albedo.value(rec.u, rec.v, &rec.point);
+        if self.fuzz <= 0.0 {
+            return (reflected.dot(&rec.normal) > 0.0).then(|| {
+                ScatterRecord::specular(
+                    Ray::at_time(rec.point, reflected, ray_in.time),
+                    attenuation,
+                )
+            });
         }
+        let direction = reflected + self.fuzz * Vec3::random_in_unit_sphere();
+        if direction.dot(&rec.normal) <= 0.0 {
+            return None;
+        }
+        Some(ScatterRecord {
+            scattered: Ray::at_time(rec.point, direction, ray_in.time),
+            attenuation,
+            pdf: self.fuzz_pdf(&reflected, &direction.unit_vec()),
+            is_specular: false,
+        })
+    }
+
+    fn eval(&self, rec: &HitRecord, wi: &Vec3, wo: &Vec3) -> Color {
+        self.albedo.value(rec.u, rec.v, &rec.point) * self.pdf(rec, wi, wo)
+    }
+
+    fn pdf(&self, rec: &HitRecord, wi: &Vec3, wo: &Vec3) -> f64 {
+        if self.fuzz <= 0.0 || wi.dot(&rec.normal) <= 0.0 {
+            return 0.0;
+        }
+        self.fuzz_pdf(&(-*wo).reflect(&rec.normal), wi)
+    }
+
+    fn has_non_specular_lobe(&self) -> bool {
+        self.fuzz > 0.0
     }
 }
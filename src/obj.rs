@@ -0,0 +1,251 @@
+//! Wavefront OBJ importer. Faces are fan-triangulated and grouped into one
+//! `TriangleMesh` per material; materials come from the `mtllib` files the OBJ
+//! references, see `mtl` for how their parameters are interpreted.
+
+mod mtl;
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    common::*,
+    hittable::{mesh::TriangleMesh, triangle::Uv},
+    material::Material,
+};
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+impl ObjError {
+    fn parse(path: &Path, line: usize, message: String) -> Self {
+        Self::Parse {
+            path: path.to_owned(),
+            line,
+            message,
+        }
+    }
+}
+
+impl Display for ObjError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "cannot read {}: {source}", path.display()),
+            Self::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{line}: {message}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Parse { .. } => None,
+        }
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, ObjError> {
+    fs::read_to_string(path).map_err(|source| ObjError::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+fn parse_floats(args: &[&str], keyword: &str) -> Result<Vec<f64>, String> {
+    args.iter()
+        .map(|arg| {
+            arg.parse()
+                .map_err(|_| format!("invalid number '{arg}' in '{keyword}'"))
+        })
+        .collect()
+}
+
+/// Resolves a 1-based, possibly negative (relative) OBJ index
+fn resolve_index(token: &str, count: usize) -> Result<usize, String> {
+    let index: i64 = token
+        .parse()
+        .map_err(|_| format!("invalid index '{token}'"))?;
+    let resolved = if index < 0 {
+        count as i64 + index
+    } else {
+        index - 1
+    };
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(format!("index {index} out of range ({count} defined)"));
+    }
+    Ok(resolved as usize)
+}
+
+/// Position, texture coordinate and normal indices of one face corner
+type Corner = (usize, Option<usize>, Option<usize>);
+
+#[derive(Default)]
+struct MeshBuilder {
+    corners: HashMap<Corner, usize>,
+    positions: Vec<Point>,
+    uvs: Vec<Option<Uv>>,
+    normals: Vec<Option<Vec3>>,
+    indices: Vec<[usize; 3]>,
+}
+
+impl MeshBuilder {
+    fn vertex(&mut self, corner: Corner, obj: &ObjData) -> usize {
+        *self.corners.entry(corner).or_insert_with(|| {
+            let (v, vt, vn) = corner;
+            self.positions.push(obj.positions[v]);
+            self.uvs.push(vt.map(|vt| obj.uvs[vt]));
+            self.normals.push(vn.map(|vn| obj.normals[vn]));
+            self.positions.len() - 1
+        })
+    }
+
+    /// Attributes are only kept if every vertex of the mesh has them
+    fn build(self, material: Arc<dyn Material>) -> TriangleMesh {
+        let uvs = self.uvs.into_iter().collect();
+        let normals = self.normals.into_iter().collect();
+        TriangleMesh::new(self.positions, self.indices, normals, uvs, material)
+    }
+}
+
+#[derive(Default)]
+struct ObjData {
+    positions: Vec<Point>,
+    uvs: Vec<Uv>,
+    normals: Vec<Vec3>,
+}
+
+impl ObjData {
+    fn parse_corner(&self, token: &str) -> Result<Corner, String> {
+        let mut parts = token.split('/');
+        let v = resolve_index(parts.next().unwrap_or_default(), self.positions.len())?;
+        let vt = match parts.next() {
+            None | Some("") => None,
+            Some(vt) => Some(resolve_index(vt, self.uvs.len())?),
+        };
+        let vn = match parts.next() {
+            None | Some("") => None,
+            Some(vn) => Some(resolve_index(vn, self.normals.len())?),
+        };
+        if parts.next().is_some() {
+            return Err(format!("malformed face vertex '{token}'"));
+        }
+        Ok((v, vt, vn))
+    }
+}
+
+/// Loads an OBJ file and the MTL libraries it references. Faces that appear
+/// before any `usemtl` get `default_material`.
+pub fn load_obj(
+    path: impl AsRef<Path>,
+    default_material: Arc<dyn Material>,
+) -> Result<Vec<TriangleMesh>, ObjError> {
+    let path = path.as_ref();
+    let text = read_file(path)?;
+    let directory = path.parent().unwrap_or(Path::new(""));
+
+    let mut obj = ObjData::default();
+    let mut materials: HashMap<String, Arc<dyn Material>> = HashMap::new();
+    // Meshes in order of first use, keyed by material name
+    let mut meshes: Vec<(Option<String>, MeshBuilder)> = vec![(None, MeshBuilder::default())];
+    let mut current = 0;
+
+    for (line_index, line) in text.lines().enumerate() {
+        let error = |message: String| ObjError::parse(path, line_index + 1, message);
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => match parse_floats(&args, keyword).map_err(error)?[..] {
+                [x, y, z] | [x, y, z, _] => obj.positions.push(Point::new(x, y, z)),
+                _ => return Err(error("'v' expects 3 coordinates".into())),
+            },
+            "vt" => match parse_floats(&args, keyword).map_err(error)?[..] {
+                [u] => obj.uvs.push((u, 0.0)),
+                [u, v] | [u, v, _] => obj.uvs.push((u, v)),
+                _ => return Err(error("'vt' expects 1 to 3 coordinates".into())),
+            },
+            "vn" => match parse_floats(&args, keyword).map_err(error)?[..] {
+                [x, y, z] if Vec3::new(x, y, z).near_zero() => {
+                    return Err(error("'vn' must not be zero".into()))
+                }
+                [x, y, z] => obj.normals.push(Vec3::new(x, y, z).unit_vec()),
+                _ => return Err(error("'vn' expects 3 coordinates".into())),
+            },
+            "f" => {
+                if args.len() < 3 {
+                    return Err(error("a face needs at least 3 vertices".into()));
+                }
+                let corners = args
+                    .iter()
+                    .map(|token| obj.parse_corner(token))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+                let mesh = &mut meshes[current].1;
+                let first = mesh.vertex(corners[0], &obj);
+                for pair in corners[1..].windows(2) {
+                    let b = mesh.vertex(pair[0], &obj);
+                    let c = mesh.vertex(pair[1], &obj);
+                    mesh.indices.push([first, b, c]);
+                }
+            }
+            "mtllib" => {
+                if args.is_empty() {
+                    return Err(error("'mtllib' expects a file name".into()));
+                }
+                for library in &args {
+                    mtl::load_mtl(&directory.join(library), &mut materials)?;
+                }
+            }
+            "usemtl" => {
+                let name = args.join(" ");
+                if !materials.contains_key(&name) {
+                    return Err(error(format!("unknown material '{name}'")));
+                }
+                current = match meshes.iter().position(|(n, _)| n.as_ref() == Some(&name)) {
+                    Some(index) => index,
+                    None => {
+                        meshes.push((Some(name), MeshBuilder::default()));
+                        meshes.len() - 1
+                    }
+                };
+            }
+            // Grouping, smoothing and free-form geometry don't affect rendering
+            _ => {}
+        }
+    }
+
+    Ok(meshes
+        .into_iter()
+        .filter(|(_, mesh)| !mesh.indices.is_empty())
+        .map(|(name, mesh)| {
+            let material = match name {
+                Some(name) => materials[&name].clone(),
+                None => default_material.clone(),
+            };
+            mesh.build(material)
+        })
+        .collect())
+}
@@ -0,0 +1,143 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use crate::{
+    common::*,
+    material::{
+        dielectric::Dielectric, diffuse_light::DiffuseLight, lambertian::Lambertian, metal::Metal,
+        Material,
+    },
+    obj::{parse_floats, read_file, ObjError},
+};
+
+/// The subset of MTL parameters that map onto our materials
+#[derive(Debug, Clone)]
+struct MtlParams {
+    diffuse: Color,
+    specular: Color,
+    emission: Color,
+    shininess: f64,
+    ior: f64,
+    dissolve: f64,
+    illum: u32,
+}
+
+impl Default for MtlParams {
+    fn default() -> Self {
+        Self {
+            diffuse: Color::new(0.8, 0.8, 0.8),
+            specular: Color::black(),
+            emission: Color::black(),
+            shininess: 0.0,
+            ior: 1.5,
+            dissolve: 1.0,
+            illum: 2,
+        }
+    }
+}
+
+impl MtlParams {
+    /// Emissive materials (non-black `Ke`) become two-sided lights. Transparent
+    /// materials (`d` < 1 or a refraction illumination model) become dielectrics,
+    /// explicitly reflective ones (`illum 3`, or black `Kd` with a non-black
+    /// `Ks`) become metals, and everything else is Lambertian.
+    fn to_material(&self) -> Arc<dyn Material> {
+        let max = |c: &Color| c.x().max(c.y()).max(c.z());
+        if max(&self.emission) > 0.0 {
+            Arc::new(DiffuseLight::two_sided(self.emission))
+        } else if self.dissolve < 1.0 || matches!(self.illum, 4 | 6 | 7 | 9) {
+            Arc::new(Dielectric::new(self.ior))
+        } else if max(&self.specular) > 0.0 && (self.illum == 3 || max(&self.diffuse) == 0.0) {
+            // Approximate Phong exponent to roughness conversion
+            let fuzz = (2.0 / (self.shininess + 2.0)).sqrt();
+            Arc::new(Metal::new(self.specular, fuzz))
+        } else {
+            Arc::new(Lambertian::new(self.diffuse))
+        }
+    }
+}
+
+pub(super) fn load_mtl(
+    path: &Path,
+    materials: &mut HashMap<String, Arc<dyn Material>>,
+) -> Result<(), ObjError> {
+    let text = read_file(path)?;
+    let mut current: Option<(String, MtlParams)> = None;
+    let mut finish = |current: Option<(String, MtlParams)>| {
+        if let Some((name, params)) = current {
+            materials.insert(name, params.to_material());
+        }
+    };
+
+    for (line_index, line) in text.lines().enumerate() {
+        let error = |message: String| ObjError::parse(path, line_index + 1, message);
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        if keyword == "newmtl" {
+            let name = args.join(" ");
+            if name.is_empty() {
+                return Err(error("newmtl requires a name".into()));
+            }
+            finish(current.take());
+            current = Some((name, MtlParams::default()));
+            continue;
+        }
+
+        let params = match current.as_mut() {
+            Some((_, params)) => params,
+            None if matches!(
+                keyword,
+                "Kd" | "Ks" | "Ke" | "Ns" | "Ni" | "d" | "Tr" | "illum"
+            ) =>
+            {
+                return Err(error(format!("'{keyword}' before any newmtl")))
+            }
+            None => continue,
+        };
+        let color = || -> Result<Color, ObjError> {
+            match parse_floats(&args, keyword).map_err(&error)?[..] {
+                [r, g, b] => Ok(Color::new(r, g, b)),
+                [v] => Ok(Color::new(v, v, v)),
+                _ => Err(error(format!("'{keyword}' expects 1 or 3 values"))),
+            }
+        };
+        let scalar = || -> Result<f64, ObjError> {
+            match parse_floats(&args, keyword).map_err(&error)?[..] {
+                [v] => Ok(v),
+                _ => Err(error(format!("'{keyword}' expects a single value"))),
+            }
+        };
+        match keyword {
+            "Kd" => params.diffuse = color()?,
+            "Ks" => params.specular = color()?,
+            "Ke" => params.emission = color()?,
+            "Ns" => {
+                params.shininess = match scalar()? {
+                    shininess if shininess < 0.0 || shininess.is_nan() => {
+                        return Err(error("'Ns' must not be negative".into()))
+                    }
+                    shininess => shininess,
+                }
+            }
+            "Ni" => params.ior = scalar()?,
+            "d" => params.dissolve = scalar()?,
+            "Tr" => params.dissolve = 1.0 - scalar()?,
+            "illum" => {
+                params.illum = match args[..] {
+                    [value] => value
+                        .parse()
+                        .map_err(|_| error(format!("invalid illumination model '{value}'")))?,
+                    _ => return Err(error("'illum' expects a single integer".into())),
+                }
+            }
+            // Ambient terms, texture maps and vendor extensions are not supported
+            _ => {}
+        }
+    }
+    finish(current);
+    Ok(())
+}
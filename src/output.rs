@@ -0,0 +1,104 @@
+pub mod exr;
+pub mod hdr;
+pub mod pfm;
+pub mod png;
+pub mod ppm;
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Seek, Write},
+    path::Path,
+};
+
+use crate::film::Film;
+use exr::ExrPrecision;
+
+/// 8-bit RGB image, stored top row first
+#[derive(Debug, Clone)]
+pub struct ImageBuffer {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl ImageBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0; 3]; width * height],
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, rgb: [u8; 3]) {
+        self.pixels[y * self.width + x] = rgb;
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.pixels.as_flattened()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Binary PPM (P6)
+    Ppm,
+    Png,
+    /// OpenEXR with linear radiance
+    Exr(ExrPrecision),
+    /// Radiance RGBE
+    Hdr,
+    /// Portable float map
+    Pfm,
+}
+
+impl OutputFormat {
+    pub const EXTENSIONS: &'static [&'static str] = &["ppm", "png", "exr", "hdr", "pfm"];
+
+    /// Picks the format from the file extension, case-insensitively. EXR files
+    /// default to half precision.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "ppm" => Some(Self::Ppm),
+            "png" => Some(Self::Png),
+            "exr" => Some(Self::Exr(ExrPrecision::Half)),
+            "hdr" => Some(Self::Hdr),
+            "pfm" => Some(Self::Pfm),
+            _ => None,
+        }
+    }
+
+    /// Whether the format stores unclamped linear radiance
+    pub fn is_hdr(&self) -> bool {
+        matches!(self, Self::Exr(_) | Self::Hdr | Self::Pfm)
+    }
+
+    /// High dynamic range formats store the film directly; 8-bit formats
+    /// store the result of `develop`.
+    pub fn write(
+        &self,
+        out: &mut (impl Write + Seek),
+        film: &Film,
+        develop: impl FnOnce(&Film) -> ImageBuffer,
+    ) -> io::Result<()> {
+        match *self {
+            Self::Ppm => ppm::write(out, &develop(film)),
+            Self::Png => png::write(out, &develop(film)),
+            Self::Exr(precision) => exr::write(out, film, precision),
+            Self::Hdr => hdr::write(out, film),
+            Self::Pfm => pfm::write(out, film),
+        }
+    }
+
+    pub fn save(
+        &self,
+        path: &Path,
+        film: &Film,
+        develop: impl FnOnce(&Film) -> ImageBuffer,
+    ) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write(&mut out, film, develop)?;
+        out.flush()
+    }
+}
@@ -0,0 +1,45 @@
+use std::io::{self, Seek, Write};
+
+use exr::prelude::{f16, Image, SpecificChannels, Vec2, WritableImage};
+
+use crate::film::Film;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExrPrecision {
+    Half,
+    Float,
+}
+
+pub fn write(
+    out: &mut (impl Write + Seek),
+    film: &Film,
+    precision: ExrPrecision,
+) -> io::Result<()> {
+    let size = (film.width, film.height);
+    let pixel = |Vec2(x, y): Vec2<usize>| film.pixel(x, y);
+    let result = match precision {
+        ExrPrecision::Half => {
+            let channels = SpecificChannels::rgb(|position| {
+                let color = pixel(position);
+                (
+                    f16::from_f64(color.x()),
+                    f16::from_f64(color.y()),
+                    f16::from_f64(color.z()),
+                )
+            });
+            Image::from_channels(size, channels)
+                .write()
+                .to_buffered(out)
+        }
+        ExrPrecision::Float => {
+            let channels = SpecificChannels::rgb(|position| {
+                let color = pixel(position);
+                (color.x() as f32, color.y() as f32, color.z() as f32)
+            });
+            Image::from_channels(size, channels)
+                .write()
+                .to_buffered(out)
+        }
+    };
+    result.map_err(io::Error::other)
+}
@@ -0,0 +1,37 @@
+use std::io::{self, Write};
+
+use crate::{common::*, film::Film};
+
+/// Shared-exponent encoding: the mantissas of all channels are scaled by the
+/// exponent of the brightest one
+fn rgbe(color: &Color) -> [u8; 4] {
+    let max = color.x().max(color.y()).max(color.z());
+    if max < 1e-32 {
+        return [0; 4];
+    }
+    // max = mantissa * 2^exponent with mantissa in [0.5, 1)
+    let exponent = max.log2().floor() as i32 + 1;
+    let scale = 256.0 / 2f64.powi(exponent);
+    let channel = |value: f64| (value.max(0.0) * scale).min(255.0) as u8;
+    [
+        channel(color.x()),
+        channel(color.y()),
+        channel(color.z()),
+        (exponent + 128).clamp(0, 255) as u8,
+    ]
+}
+
+/// Radiance RGBE with flat (not run-length encoded) scanlines
+pub fn write(out: &mut impl Write, film: &Film) -> io::Result<()> {
+    write!(
+        out,
+        "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+        film.height, film.width
+    )?;
+    for y in 0..film.height {
+        for x in 0..film.width {
+            out.write_all(&rgbe(&film.pixel(x, y)))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use std::io::{self, Write};
+
+use crate::film::Film;
+
+/// Portable float map: little-endian (negative scale) f32 RGB, bottom row first
+pub fn write(out: &mut impl Write, film: &Film) -> io::Result<()> {
+    write!(out, "PF\n{} {}\n-1.0\n", film.width, film.height)?;
+    for y in (0..film.height).rev() {
+        for x in 0..film.width {
+            let color = film.pixel(x, y);
+            for channel in [color.x(), color.y(), color.z()] {
+                out.write_all(&(channel as f32).to_le_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,15 @@
+use std::io::{self, Write};
+
+use crate::output::ImageBuffer;
+
+pub fn write(out: &mut impl Write, image: &ImageBuffer) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(out, image.width as u32, image.height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer
+        .write_image_data(image.as_bytes())
+        .map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)
+}
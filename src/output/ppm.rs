@@ -0,0 +1,8 @@
+use std::io::{self, Write};
+
+use crate::output::ImageBuffer;
+
+pub fn write(out: &mut impl Write, image: &ImageBuffer) -> io::Result<()> {
+    write!(out, "P6\n{} {}\n255\n", image.width, image.height)?;
+    out.write_all(image.as_bytes())
+}
@@ -0,0 +1,327 @@
+use std::{
+    ops::AddAssign,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::{
+    common::*,
+    film::Film,
+    hittable::{HitRecord, Hittable},
+    scene::{RenderSettings, Scene},
+    tile::{TileOrder, TileQueue},
+};
+
+/// Weight of a sample from a strategy with density `f` when another strategy
+/// with density `g` could have produced it
+fn power_heuristic(f: f64, g: f64) -> f64 {
+    let (f2, g2) = (f * f, g * g);
+    if f2 + g2 == 0.0 {
+        0.0
+    } else {
+        f2 / (f2 + g2)
+    }
+}
+
+/// Counters describing how the traced paths ended
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PathStats {
+    pub paths: u64,
+    /// Scattering events over all paths
+    pub bounces: u64,
+    /// Paths that left the scene
+    pub escaped: u64,
+    /// Paths ended by a material that doesn't scatter, e.g. a light
+    pub absorbed: u64,
+    pub roulette_terminated: u64,
+    pub max_depth_reached: u64,
+    /// Sum of the luminance of each path's throughput when it ended, before
+    /// any roulette decision
+    pub final_throughput: f64,
+}
+
+impl PathStats {
+    pub fn mean_bounces(&self) -> f64 {
+        self.bounces as f64 / self.paths.max(1) as f64
+    }
+
+    pub fn mean_final_throughput(&self) -> f64 {
+        self.final_throughput / self.paths.max(1) as f64
+    }
+}
+
+impl AddAssign for PathStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.paths += rhs.paths;
+        self.bounces += rhs.bounces;
+        self.escaped += rhs.escaped;
+        self.absorbed += rhs.absorbed;
+        self.roulette_terminated += rhs.roulette_terminated;
+        self.max_depth_reached += rhs.max_depth_reached;
+        self.final_throughput += rhs.final_throughput;
+    }
+}
+
+/// Radiance arriving along `ray`, following at most `max_depth` bounces with
+/// Russian roulette after `min_bounces` from the scene's settings
+pub fn ray_color(ray: &Ray, scene: &Scene, stats: &mut PathStats) -> Color {
+    let RenderSettings {
+        max_depth,
+        min_bounces,
+        ..
+    } = scene.settings;
+    stats.paths += 1;
+
+    let mut ray = *ray;
+    let mut radiance = Color::black();
+    let mut throughput = Color::white();
+    // Density with which the previous bounce sampled `ray`, or `None` for
+    // camera rays and specular bounces that lights cannot compete with
+    let mut scatter_pdf: Option<f64> = None;
+    for bounce in 0..max_depth {
+        let Some(hit_rec) = scene.hit(&ray, 0.001) else {
+            let mut emitted = scene.environment.radiance(&ray.direction);
+            if let Some(scatter_pdf) = scatter_pdf {
+                let light_pdf = scene.environment.pdf(&ray.direction);
+                emitted = emitted * power_heuristic(scatter_pdf, light_pdf);
+            }
+            radiance += throughput * emitted;
+            stats.escaped += 1;
+            stats.final_throughput += throughput.luminance();
+            return radiance;
+        };
+        let material = &hit_rec.material;
+
+        let mut emitted = material.emitted(&ray, &hit_rec);
+        if let Some(scatter_pdf) = scatter_pdf {
+            let light_pdf = scene
+                .emitters
+                .pdf_value(&ray.origin, &ray.direction, ray.time);
+            emitted = emitted * power_heuristic(scatter_pdf, light_pdf);
+        }
+        radiance += throughput * emitted;
+
+        let scattered = material.scatter(&ray, &hit_rec);
+        // Direct light doesn't depend on the scattered direction, so it is
+        // gathered even if that sample was absorbed
+        if material.has_non_specular_lobe()
+            && !scattered.as_ref().is_some_and(|rec| rec.is_specular)
+        {
+            radiance += throughput
+                * (sample_light(&ray, scene, &hit_rec)
+                    + sample_environment(&ray, scene, &hit_rec)
+                    + sample_lights(&ray, scene, &hit_rec));
+        }
+        let Some(mat_rec) = scattered else {
+            stats.absorbed += 1;
+            stats.final_throughput += throughput.luminance();
+            return radiance;
+        };
+        scatter_pdf = (!mat_rec.is_specular).then_some(mat_rec.pdf);
+        throughput *= mat_rec.attenuation;
+        ray = mat_rec.scattered;
+        stats.bounces += 1;
+
+        // Continue with probability proportional to the throughput and
+        // compensate survivors, keeping the estimate unbiased
+        if bounce + 1 >= min_bounces {
+            let survival = throughput.max_component().min(1.0);
+            if random::unit() >= survival {
+                stats.roulette_terminated += 1;
+                stats.final_throughput += throughput.luminance();
+                return radiance;
+            }
+            throughput = throughput / survival;
+        }
+    }
+    stats.max_depth_reached += 1;
+    stats.final_throughput += throughput.luminance();
+    radiance
+}
+
+/// Next event estimation: radiance reflected towards `ray` from a point
+/// sampled on the scene's emitters, weighted against scattering. Media along
+/// the shadow ray attenuate the light rather than block it.
+fn sample_light(ray: &Ray, scene: &Scene, hit_rec: &HitRecord) -> Color {
+    if scene.emitters.objects.is_empty() {
+        return Color::black();
+    }
+    let direction = scene.emitters.random(&hit_rec.point, ray.time);
+    let light_pdf = scene
+        .emitters
+        .pdf_value(&hit_rec.point, &direction, ray.time);
+    if light_pdf <= 0.0 {
+        return Color::black();
+    }
+    light_sample(ray, hit_rec, &direction, light_pdf, |shadow_ray| {
+        let light_rec = scene.emitters.hit(shadow_ray, 0.001, f64::INFINITY)?;
+        // Stop just short of the light so that it doesn't occlude itself
+        let transmittance = scene.transmittance(shadow_ray, 0.001, light_rec.time * (1.0 - 1e-4));
+        (transmittance > 0.0)
+            .then(|| transmittance * light_rec.material.emitted(shadow_ray, &light_rec))
+    })
+}
+
+/// Next event estimation towards the environment, weighted against
+/// scattering
+fn sample_environment(ray: &Ray, scene: &Scene, hit_rec: &HitRecord) -> Color {
+    let Some(direction) = scene.environment.sample() else {
+        return Color::black();
+    };
+    let light_pdf = scene.environment.pdf(&direction);
+    if light_pdf <= 0.0 {
+        return Color::black();
+    }
+    light_sample(ray, hit_rec, &direction, light_pdf, |shadow_ray| {
+        let transmittance = scene.transmittance(shadow_ray, 0.001, f64::INFINITY);
+        (transmittance > 0.0)
+            .then(|| transmittance * scene.environment.radiance(&shadow_ray.direction))
+    })
+}
+
+/// Radiance reflected towards `ray` from every point, spot and directional
+/// light. Scattering can never find them, so they are added in full.
+fn sample_lights(ray: &Ray, scene: &Scene, hit_rec: &HitRecord) -> Color {
+    let wo = -ray.direction.unit_vec();
+    let mut radiance = Color::black();
+    for light in &scene.lights {
+        let Some(sample) = light.sample(&hit_rec.point) else {
+            continue;
+        };
+        let f = hit_rec.material.eval(hit_rec, &sample.direction, &wo);
+        if f.near_zero() {
+            continue;
+        }
+        let shadow_ray = Ray::at_time(hit_rec.point, sample.direction, ray.time);
+        let transmittance = scene.transmittance(&shadow_ray, 0.001, sample.distance);
+        radiance += transmittance * f * sample.irradiance;
+    }
+    radiance
+}
+
+/// Light reflected towards `ray` from `direction`, sampled with density
+/// `light_pdf`. `incoming` traces the shadow ray and returns the radiance
+/// reaching the hit, only called if the material reflects any of it.
+fn light_sample(
+    ray: &Ray,
+    hit_rec: &HitRecord,
+    direction: &Vec3,
+    light_pdf: f64,
+    incoming: impl FnOnce(&Ray) -> Option<Color>,
+) -> Color {
+    let wi = direction.unit_vec();
+    let wo = -ray.direction.unit_vec();
+    let material = &hit_rec.material;
+    let f = material.eval(hit_rec, &wi, &wo);
+    if f.near_zero() {
+        return Color::black();
+    }
+    let shadow_ray = Ray::at_time(hit_rec.point, *direction, ray.time);
+    let Some(radiance) = incoming(&shadow_ray) else {
+        return Color::black();
+    };
+    let scatter_pdf = material.pdf(hit_rec, &wi, &wo);
+    f * radiance * (power_heuristic(light_pdf, scatter_pdf) / light_pdf)
+}
+
+/// Renders scenes into a film on a pool of worker threads pulling tiles from
+/// a shared queue
+#[derive(Debug, Clone)]
+pub struct Renderer {
+    pub threads: usize,
+    pub tile_size: usize,
+    pub tile_order: TileOrder,
+    /// Seed from which every pixel sample derives its random numbers, making
+    /// renders reproducible regardless of thread count; drawn from system
+    /// entropy if not set
+    pub seed: Option<u64>,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self {
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            tile_size: 32,
+            tile_order: TileOrder::Spiral,
+            seed: None,
+        }
+    }
+}
+
+/// The image a render produced, with statistics over all its paths
+#[derive(Debug, Clone)]
+pub struct RenderOutput {
+    pub film: Film,
+    pub stats: PathStats,
+}
+
+impl Renderer {
+    pub fn render(&self, scene: &Scene) -> RenderOutput {
+        self.render_with_progress(scene, |_, _| {})
+    }
+
+    /// Renders the scene, calling `progress` with the number of finished and
+    /// total tiles whenever a tile completes
+    pub fn render_with_progress(
+        &self,
+        scene: &Scene,
+        progress: impl Fn(usize, usize) + Sync,
+    ) -> RenderOutput {
+        let RenderSettings {
+            image_width,
+            image_height,
+            samples_per_pixel,
+            ..
+        } = scene.settings;
+
+        let output = Mutex::new(RenderOutput {
+            film: Film::new(image_width, image_height),
+            stats: PathStats::default(),
+        });
+        let tiles = TileQueue::new(image_width, image_height, self.tile_size, self.tile_order);
+        let counter = AtomicUsize::new(0);
+        let seed = self.seed.unwrap_or_else(random::entropy_seed);
+        thread::scope(|s| {
+            for _ in 0..self.threads.max(1) {
+                let (output, tiles, counter, progress) = (&output, &tiles, &counter, &progress);
+                s.spawn(move || {
+                    let mut res = vec![];
+                    while let Some(tile) = tiles.claim() {
+                        res.clear();
+                        let mut stats = PathStats::default();
+                        for (x, y) in tile.pixels() {
+                            // The film stores the top row first, the camera expects v to point up
+                            let iy = image_height - 1 - y;
+                            let mut pixel_color = Color::new(0.0, 0.0, 0.0);
+                            for sample in 0..samples_per_pixel {
+                                random::seed(random::derive_seed(
+                                    seed,
+                                    &[x as u64, y as u64, sample as u64],
+                                ));
+                                let u =
+                                    (x as f64 + random::unit()) / (image_width.max(2) - 1) as f64;
+                                let v =
+                                    (iy as f64 + random::unit()) / (image_height.max(2) - 1) as f64;
+                                let ray = scene.camera.get_ray(u, v);
+                                pixel_color += ray_color(&ray, scene, &mut stats);
+                            }
+                            res.push(pixel_color);
+                        }
+                        let mut output = output.lock().unwrap();
+                        for ((x, y), color) in tile.pixels().zip(&res) {
+                            output.film.add_samples(x, y, *color, samples_per_pixel);
+                        }
+                        output.stats += stats;
+                        drop(output);
+                        let done = counter.fetch_add(1, Ordering::Relaxed) + 1;
+                        progress(done, tiles.len());
+                    }
+                });
+            }
+        });
+        output.into_inner().unwrap()
+    }
+}
@@ -0,0 +1,81 @@
+pub mod builtin;
+pub mod description;
+
+use std::sync::Arc;
+
+use crate::{
+    camera::Camera,
+    common::*,
+    environment::Environment,
+    hittable::{bvh::Bvh, constant_medium::Atmosphere, hit_list::HitList, HitRecord, Hittable},
+    light::Light,
+};
+
+#[derive(Debug, Clone)]
+pub struct RenderSettings {
+    pub image_width: usize,
+    pub image_height: usize,
+    pub samples_per_pixel: u32,
+    pub max_depth: usize,
+    /// Bounces before Russian roulette may terminate a path
+    pub min_bounces: usize,
+}
+
+impl RenderSettings {
+    pub fn aspect_ratio(&self) -> f64 {
+        self.image_width as f64 / self.image_height as f64
+    }
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            image_width: 1200,
+            image_height: 800,
+            samples_per_pixel: 10,
+            max_depth: 50,
+            min_bounces: 3,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Scene {
+    pub world: Bvh,
+    /// Emissive objects, also part of `world`, sampled for direct lighting
+    pub emitters: HitList,
+    /// Point, spot and directional lights, which are not part of `world`
+    pub lights: Vec<Arc<dyn Light>>,
+    pub camera: Camera,
+    /// Light from outside the scene, seen by escaping rays
+    pub environment: Arc<dyn Environment>,
+    /// Fog filling the space between surfaces
+    pub atmosphere: Option<Atmosphere>,
+    pub settings: RenderSettings,
+}
+
+impl Scene {
+    /// Nearest interaction along `ray`, either with a surface or, in front of
+    /// it, with the atmosphere
+    pub fn hit(&self, ray: &Ray, t_min: f64) -> Option<HitRecord> {
+        let surface = self.world.hit(ray, t_min, f64::INFINITY)?;
+        self.atmosphere
+            .as_ref()
+            .and_then(|atmosphere| atmosphere.scatter(ray, t_min, &surface))
+            .or(Some(surface))
+    }
+
+    /// Fraction of light getting through the media and past the surfaces
+    /// along `ray` between `t_min` and `t_max`. With an infinite `t_max` the
+    /// atmosphere is left out, as it doesn't attenuate escaping rays either.
+    pub fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64) -> f64 {
+        let mut transmittance = self.world.transmittance(ray, t_min, t_max);
+        if let Some(atmosphere) = &self.atmosphere {
+            if transmittance > 0.0 && t_max.is_finite() {
+                let distance = (t_max - t_min) * ray.direction.length();
+                transmittance *= atmosphere.transmittance(distance);
+            }
+        }
+        transmittance
+    }
+}
@@ -0,0 +1,345 @@
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use rand_distr::Pareto;
+
+use crate::{
+    camera::Camera,
+    common::*,
+    environment::{gradient::Gradient, sky::Sky, uniform::Uniform},
+    hittable::{
+        constant_medium::ConstantMedium,
+        hit_list::HitList,
+        moving_sphere::MovingSphere,
+        quad::{Cuboid, Quad},
+        sphere::Sphere,
+        transform::Transformed,
+    },
+    material::{
+        dielectric::Dielectric, diffuse_light::DiffuseLight, lambertian::Lambertian, metal::Metal,
+        Material,
+    },
+    scene::{RenderSettings, Scene},
+};
+
+/// Scenes that are constructed in code rather than loaded from a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Builtin {
+    Random,
+    /// The random scene with its matte spheres bouncing while the shutter is
+    /// open
+    BouncingSpheres,
+    /// The random scene in the afternoon sun under a clear sky
+    Daylight,
+    /// Matte and glass spheres lit only by a spherical area light
+    SimpleLight,
+    /// The Cornell box with two white boxes under a ceiling light
+    CornellBox,
+    /// The Cornell box with boxes of dark and light smoke
+    CornellSmoke,
+}
+
+impl Builtin {
+    pub const ALL: &'static [Builtin] = &[
+        Builtin::Random,
+        Builtin::BouncingSpheres,
+        Builtin::Daylight,
+        Builtin::SimpleLight,
+        Builtin::CornellBox,
+        Builtin::CornellSmoke,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Builtin::Random => "random",
+            Builtin::BouncingSpheres => "bouncing_spheres",
+            Builtin::Daylight => "daylight",
+            Builtin::SimpleLight => "simple_light",
+            Builtin::CornellBox => "cornell_box",
+            Builtin::CornellSmoke => "cornell_smoke",
+        }
+    }
+
+    /// Settings the scene is meant to be rendered with
+    pub fn settings(&self) -> RenderSettings {
+        match self {
+            Builtin::Random
+            | Builtin::BouncingSpheres
+            | Builtin::Daylight
+            | Builtin::SimpleLight => RenderSettings::default(),
+            Builtin::CornellBox | Builtin::CornellSmoke => RenderSettings {
+                image_width: 600,
+                image_height: 600,
+                samples_per_pixel: 64,
+                ..RenderSettings::default()
+            },
+        }
+    }
+
+    pub fn build(&self, settings: RenderSettings) -> Scene {
+        match self {
+            Builtin::Random => random_spheres(settings, false),
+            Builtin::BouncingSpheres => random_spheres(settings, true),
+            Builtin::Daylight => Scene {
+                environment: Arc::new(Sky::new(30.0, 70.0, 3.0)),
+                ..random_spheres(settings, false)
+            },
+            Builtin::SimpleLight => simple_light(settings),
+            Builtin::CornellBox => cornell_box(settings, false),
+            Builtin::CornellSmoke => cornell_box(settings, true),
+        }
+    }
+}
+
+impl Display for Builtin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Builtin {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|builtin| builtin.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(Builtin::name).collect();
+                format!("unknown scene '{s}', expected one of: {}", names.join(", "))
+            })
+    }
+}
+
+/// The cover image of Ray Tracing in One Weekend, optionally with the matte
+/// spheres moving up during the exposure
+fn random_spheres(settings: RenderSettings, bouncing: bool) -> Scene {
+    let mut world = HitList::default();
+    let ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    world.add(Arc::new(Sphere::new(
+        Point::new(0.0, -1000.0, 0.0),
+        1000.0,
+        ground,
+    )));
+    for a in -11..11 {
+        for b in -11..11 {
+            let material_choice = random::unit();
+            let size: f64 = random::sample(Pareto::new(1., 2.).unwrap()) * 0.07;
+            let size = size.min(0.6);
+            let center = Point::new(
+                a as f64 + 0.9 * random::unit(),
+                size,
+                b as f64 + 0.9 * random::unit(),
+            );
+            if (center - Point::new(4.0, size, 0.0)).length() > 0.9 {
+                let mat: Arc<dyn Material> = if material_choice < 0.8 {
+                    let albedo = Color::random_unit() * Color::random_unit();
+                    let mat = Arc::new(Lambertian::new(albedo));
+                    if bouncing {
+                        let center1 = center + Vec3::new(0.0, random::range(0.0, 0.5), 0.0);
+                        world.add(Arc::new(MovingSphere::new(center, center1, size, mat)));
+                        continue;
+                    }
+                    mat
+                } else if material_choice < 0.95 {
+                    let albedo = Color::random(0.5, 1.0);
+                    let fuzz = random::range(0.0, 0.5);
+                    Arc::new(Metal::new(albedo, fuzz))
+                } else {
+                    Arc::new(Dielectric::new(1.5))
+                };
+                world.add(Arc::new(Sphere::new(center, size, mat)));
+            }
+        }
+    }
+    world.add(Arc::new(Sphere::new(
+        Point::new(0.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point::new(-4.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::new(Color::new(0.4, 0.2, 0.1))),
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point::new(4.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0)),
+    )));
+
+    let lookfrom = Point::new(13.0, 2.0, 3.0);
+    let lookat = Point::new(0.0, 0.0, 0.0);
+    let vup = Vec3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 10.0;
+    let aperture = 0.1;
+    let camera = Camera::new(
+        lookfrom,
+        lookat,
+        vup,
+        20.0,
+        settings.aspect_ratio(),
+        aperture,
+        dist_to_focus,
+    )
+    .with_shutter(0.0, if bouncing { 1.0 } else { 0.0 });
+    Scene {
+        world: world.into(),
+        emitters: HitList::default(),
+        lights: Vec::new(),
+        camera,
+        environment: Arc::new(Gradient::default()),
+        atmosphere: None,
+        settings,
+    }
+}
+
+fn simple_light(settings: RenderSettings) -> Scene {
+    let mut world = HitList::default();
+    world.add(Arc::new(Sphere::new(
+        Point::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point::new(-1.2, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::new(Color::new(0.7, 0.3, 0.2))),
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point::new(1.2, 1.0, 0.0),
+        1.0,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+    let light = Arc::new(Sphere::new(
+        Point::new(0.0, 4.0, 1.5),
+        0.8,
+        Arc::new(DiffuseLight::new(Color::new(8.0, 8.0, 8.0))),
+    ));
+    world.add(light.clone());
+    let emitters = HitList::new(light);
+
+    let lookfrom = Point::new(0.0, 2.0, 9.0);
+    let lookat = Point::new(0.0, 1.0, 0.0);
+    let camera = Camera::new(
+        lookfrom,
+        lookat,
+        Vec3::new(0.0, 1.0, 0.0),
+        30.0,
+        settings.aspect_ratio(),
+        0.0,
+        (lookfrom - lookat).length(),
+    );
+    Scene {
+        world: world.into(),
+        emitters,
+        lights: Vec::new(),
+        camera,
+        environment: Arc::new(Uniform::black()),
+        atmosphere: None,
+        settings,
+    }
+}
+
+/// The Cornell box, optionally with the boxes made of dark and light smoke
+fn cornell_box(settings: RenderSettings, smoke: bool) -> Scene {
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::new(Color::new(15.0, 15.0, 15.0)));
+
+    let mut world = HitList::default();
+    world.add(Arc::new(Quad::yz_rect(
+        0.0, 555.0, 0.0, 555.0, 555.0, green,
+    )));
+    world.add(Arc::new(Quad::yz_rect(0.0, 555.0, 0.0, 555.0, 0.0, red)));
+    world.add(Arc::new(Quad::xz_rect(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        white.clone(),
+    )));
+    world.add(Arc::new(Quad::xz_rect(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    world.add(Arc::new(Quad::xy_rect(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    // Facing down into the room
+    let light = Arc::new(Quad::xz_rect(213.0, 343.0, 227.0, 332.0, 554.0, light).flipped());
+    world.add(light.clone());
+
+    let up = Vec3::new(0.0, 1.0, 0.0);
+    let tall_box = Cuboid::new(
+        Point::new(0.0, 0.0, 0.0),
+        Point::new(165.0, 330.0, 165.0),
+        white.clone(),
+    );
+    let tall_box = Arc::new(
+        Transformed::new(
+            Arc::new(tall_box),
+            Mat4::translation(&Vec3::new(265.0, 0.0, 295.0)) * Mat4::rotation(&up, 15.0),
+        )
+        .expect("rigid transforms are invertible"),
+    );
+    let short_box = Cuboid::new(
+        Point::new(0.0, 0.0, 0.0),
+        Point::new(165.0, 165.0, 165.0),
+        white,
+    );
+    let short_box = Arc::new(
+        Transformed::new(
+            Arc::new(short_box),
+            Mat4::translation(&Vec3::new(130.0, 0.0, 65.0)) * Mat4::rotation(&up, -18.0),
+        )
+        .expect("rigid transforms are invertible"),
+    );
+    if smoke {
+        world.add(Arc::new(ConstantMedium::isotropic(
+            tall_box,
+            0.01,
+            Color::black(),
+        )));
+        world.add(Arc::new(ConstantMedium::isotropic(
+            short_box,
+            0.01,
+            Color::white(),
+        )));
+    } else {
+        world.add(tall_box);
+        world.add(short_box);
+    }
+
+    let lookfrom = Point::new(278.0, 278.0, -800.0);
+    let lookat = Point::new(278.0, 278.0, 0.0);
+    let camera = Camera::new(
+        lookfrom,
+        lookat,
+        Vec3::new(0.0, 1.0, 0.0),
+        40.0,
+        settings.aspect_ratio(),
+        0.0,
+        (lookfrom - lookat).length(),
+    );
+    Scene {
+        world: world.into(),
+        emitters: HitList::new(light),
+        lights: Vec::new(),
+        camera,
+        environment: Arc::new(Uniform::black()),
+        atmosphere: None,
+        settings,
+    }
+}
@@ -0,0 +1,1094 @@
+//! TOML scene files. A scene has `[image]`, `[render]`, `[camera]` and
+//! optionally `[background]` and `[atmosphere]` tables, named `[textures.<name>]` and
+//! `[materials.<name>]`, a list of `[[objects]]` referring to them and a list
+//! of point, spot and directional `[[lights]]`:
+//!
+//! ```toml
+//! [image]
+//! width = 400
+//! height = 300
+//!
+//! [camera]
+//! lookfrom = [0, 1, 5]
+//! lookat = [0, 1, 0]
+//! vertical_fov = 40
+//!
+//! [materials.red]
+//! type = "lambertian"
+//! albedo = [0.8, 0.1, 0.1]
+//!
+//! [[objects]]
+//! type = "sphere"
+//! center = [0, 1, 0]
+//! radius = 1
+//! material = "red"
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs, io,
+    num::{NonZeroU32, NonZeroUsize},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+use toml::Spanned;
+
+use crate::{
+    camera::Camera,
+    common::*,
+    environment::{
+        gradient::Gradient, map::EnvironmentMap, sky::Sky, uniform::Uniform, Environment,
+    },
+    hittable::{
+        constant_medium::{Atmosphere, ConstantMedium},
+        grid_medium::GridMedium,
+        hit_list::HitList,
+        moving_sphere::MovingSphere,
+        quad::{Cuboid, Quad},
+        sphere::Sphere,
+        transform::{TransformStep, Transformed},
+        triangle::Triangle,
+        Hittable,
+    },
+    light::{directional::DirectionalLight, point::PointLight, spot::SpotLight, Light},
+    material::{
+        conductor::{Conductor, Preset},
+        dielectric::Dielectric,
+        diffuse_light::DiffuseLight,
+        henyey_greenstein::HenyeyGreenstein,
+        lambertian::Lambertian,
+        metal::Metal,
+        Material,
+    },
+    obj::load_obj,
+    scene::{RenderSettings, Scene},
+    texture::{
+        checker::Checker, image::ImageTexture, noise::NoiseTexture, solid::SolidColor, Texture,
+    },
+    volume::load_vol,
+};
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// Syntax or semantic error; `location` is the 1-based line and column
+    /// when the description was parsed from text
+    Invalid {
+        path: PathBuf,
+        location: Option<(usize, usize)>,
+        message: String,
+    },
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "cannot read {}: {source}", path.display()),
+            Self::Invalid {
+                path,
+                location: Some((line, column)),
+                message,
+            } => write!(f, "{}:{line}:{column}: {message}", path.display()),
+            Self::Invalid {
+                path,
+                location: None,
+                message,
+            } => write!(f, "{}: {message}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Invalid { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImageDescription {
+    pub width: NonZeroUsize,
+    pub height: NonZeroUsize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RenderDescription {
+    pub samples_per_pixel: NonZeroU32,
+    pub max_depth: usize,
+    pub min_bounces: usize,
+}
+
+impl Default for RenderDescription {
+    fn default() -> Self {
+        let settings = RenderSettings::default();
+        Self {
+            samples_per_pixel: NonZeroU32::new(settings.samples_per_pixel).unwrap(),
+            max_depth: settings.max_depth,
+            min_bounces: settings.min_bounces,
+        }
+    }
+}
+
+fn default_one() -> f64 {
+    1.0
+}
+
+fn default_turbidity() -> f64 {
+    3.0
+}
+
+fn default_vup() -> Vec3 {
+    Vec3::new(0.0, 1.0, 0.0)
+}
+
+fn default_tangent() -> Vec3 {
+    Vec3::new(1.0, 0.0, 0.0)
+}
+
+/// Parameters of `Camera::new`; the aspect ratio comes from the image size.
+/// `focus_dist` defaults to the distance between `lookfrom` and `lookat`.
+/// The shutter is instantaneous at time 0 unless `shutter_close` is set, e.g.
+/// to 1 to expose the whole motion of moving objects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CameraDescription {
+    pub lookfrom: Point,
+    pub lookat: Point,
+    #[serde(default = "default_vup")]
+    pub vup: Vec3,
+    pub vertical_fov: f64,
+    #[serde(default)]
+    pub aperture: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_dist: Option<f64>,
+    #[serde(default)]
+    pub shutter_open: f64,
+    #[serde(default)]
+    pub shutter_close: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum TextureDescription {
+    Solid {
+        color: Color,
+    },
+    /// World-space checkerboard with cells of side `scale`
+    Checker {
+        scale: f64,
+        even: Color,
+        odd: Color,
+    },
+    /// PNG, PPM, Radiance HDR or OpenEXR file, relative to the scene file
+    Image {
+        path: PathBuf,
+    },
+    /// Perlin noise marble
+    Noise {
+        scale: f64,
+        #[serde(default = "Color::white")]
+        color: Color,
+    },
+}
+
+/// Either a constant color or the name of an entry in `[textures]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AlbedoDescription {
+    Color(Color),
+    Texture(String),
+}
+
+/// Index of refraction of a conductor, either the name of a preset like
+/// `"gold"` or `{ eta = [...], k = [...] }`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum IorDescription {
+    Preset(Preset),
+    Complex { eta: Color, k: Color },
+}
+
+/// Either one roughness or different ones along the conductor's `tangent`
+/// and across it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RoughnessDescription {
+    Isotropic(f64),
+    Anisotropic([f64; 2]),
+}
+
+impl RoughnessDescription {
+    fn pair(&self) -> [f64; 2] {
+        match *self {
+            RoughnessDescription::Isotropic(roughness) => [roughness; 2],
+            RoughnessDescription::Anisotropic(pair) => pair,
+        }
+    }
+}
+
+impl Default for RoughnessDescription {
+    fn default() -> Self {
+        Self::Isotropic(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum MaterialDescription {
+    Lambertian {
+        albedo: AlbedoDescription,
+    },
+    Metal {
+        albedo: AlbedoDescription,
+        #[serde(default)]
+        fuzz: f64,
+    },
+    Dielectric {
+        refraction_index: f64,
+    },
+    /// Microfacet metal, a mirror with the default roughness of 0. A pair of
+    /// roughnesses applies along and across `tangent`, a world-space
+    /// direction projected onto the surface.
+    Conductor {
+        ior: IorDescription,
+        #[serde(default)]
+        roughness: RoughnessDescription,
+        #[serde(default = "default_tangent")]
+        tangent: Vec3,
+    },
+    DiffuseLight {
+        emit: Color,
+        #[serde(default)]
+        two_sided: bool,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ObjectDescription {
+    Sphere {
+        center: Point,
+        radius: f64,
+        material: String,
+    },
+    /// Sphere moving from `center0` at time 0 to `center1` at time 1
+    MovingSphere {
+        center0: Point,
+        center1: Point,
+        radius: f64,
+        material: String,
+    },
+    Triangle {
+        vertices: [Point; 3],
+        material: String,
+    },
+    /// Parallelogram with corner `q` and edges `u` and `v`, facing `u × v`
+    Quad {
+        q: Point,
+        u: Vec3,
+        v: Vec3,
+        material: String,
+    },
+    /// Axis-aligned box between two opposite corners
+    Box {
+        min: Point,
+        max: Point,
+        material: String,
+    },
+    /// OBJ file, relative to the scene file. `material` is used for faces
+    /// without a `usemtl`.
+    Mesh { path: PathBuf, material: String },
+    /// Another object placed by the `transform` steps, applied in order. With
+    /// `transform_end` the instance moves from `transform` at time 0 to
+    /// `transform_end` at time 1, which must have the same kinds of steps.
+    Instance {
+        transform: Vec<TransformStep>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        transform_end: Option<Vec<TransformStep>>,
+        object: Box<ObjectDescription>,
+    },
+    /// Fog or smoke of constant `density` filling the closed, convex
+    /// `boundary`, whose own material is ignored. `anisotropy` is the
+    /// Henyey-Greenstein `g` of the phase function, positive for forward
+    /// scattering.
+    ConstantMedium {
+        boundary: Box<ObjectDescription>,
+        density: f64,
+        albedo: Color,
+        #[serde(default)]
+        anisotropy: f64,
+    },
+    /// Smoke or clouds from a `.vol` voxel grid, relative to the scene file,
+    /// with its densities multiplied by `density`
+    GridMedium {
+        path: PathBuf,
+        #[serde(default = "default_one")]
+        density: f64,
+        albedo: Color,
+        #[serde(default)]
+        anisotropy: f64,
+    },
+}
+
+/// Lights that aren't objects in the scene. Point and spot lights shine
+/// `intensity` from `position`, which falls off with the square of the
+/// distance; directional lights have the same `irradiance` everywhere.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum LightDescription {
+    Point {
+        position: Point,
+        intensity: Color,
+    },
+    /// Shines along `direction` into a cone `cone_angle` degrees wide from
+    /// its axis, fading out from `falloff_start` degrees, by default the
+    /// same as `cone_angle` for a hard edge
+    Spot {
+        position: Point,
+        direction: Vec3,
+        intensity: Color,
+        cone_angle: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        falloff_start: Option<f64>,
+    },
+    /// Parallel light travelling along `direction`
+    Directional {
+        direction: Vec3,
+        irradiance: Color,
+    },
+}
+
+/// Phase function of a medium scattering `albedo` of the light
+fn phase_function(albedo: Color, anisotropy: f64) -> Arc<dyn Material> {
+    Arc::new(HenyeyGreenstein::new(albedo, anisotropy))
+}
+
+/// Light from outside the scene, by default the sky of Ray Tracing in One
+/// Weekend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum BackgroundDescription {
+    /// Vertical blend from `horizon` looking down to `zenith` looking up
+    Gradient {
+        horizon: Color,
+        zenith: Color,
+    },
+    Solid {
+        color: Color,
+    },
+    /// Equirectangular image, usually `.hdr` or `.exr`, relative to the scene
+    /// file and turned counterclockwise seen from above by `rotation` degrees
+    Map {
+        path: PathBuf,
+        #[serde(default)]
+        rotation: f64,
+        #[serde(default = "default_one")]
+        intensity: f64,
+    },
+    /// Daylight of the Preetham sky model with the sun `sun_elevation`
+    /// degrees above the horizon and `sun_azimuth` degrees counterclockwise
+    /// from +x seen from above. `turbidity` ranges from 2 for very clear to
+    /// 10 for hazy air.
+    Sky {
+        sun_elevation: f64,
+        #[serde(default)]
+        sun_azimuth: f64,
+        #[serde(default = "default_turbidity")]
+        turbidity: f64,
+        #[serde(default = "default_one")]
+        intensity: f64,
+    },
+}
+
+/// Homogeneous fog between all surfaces, see `Atmosphere`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AtmosphereDescription {
+    pub density: f64,
+    #[serde(default = "Color::white")]
+    pub albedo: Color,
+}
+
+/// A hittable built from an object description, with its material
+type Built = (Arc<dyn Hittable>, Arc<dyn Material>);
+
+/// Where the description was read from, used to turn spans into line numbers
+#[derive(Debug, Clone, Default)]
+struct Source {
+    path: PathBuf,
+    text: String,
+}
+
+impl Source {
+    fn error(&self, span: Option<Range<usize>>, message: impl Into<String>) -> SceneError {
+        let location = span.filter(|_| !self.text.is_empty()).map(|span| {
+            let before = &self.text[..span.start.min(self.text.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+            (line, column)
+        });
+        SceneError::Invalid {
+            path: self.path.clone(),
+            location,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SceneDescription {
+    pub image: ImageDescription,
+    #[serde(default)]
+    pub render: RenderDescription,
+    pub camera: Spanned<CameraDescription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<Spanned<BackgroundDescription>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atmosphere: Option<Spanned<AtmosphereDescription>>,
+    #[serde(default)]
+    pub textures: BTreeMap<String, Spanned<TextureDescription>>,
+    #[serde(default)]
+    pub materials: BTreeMap<String, Spanned<MaterialDescription>>,
+    #[serde(default)]
+    pub objects: Vec<Spanned<ObjectDescription>>,
+    #[serde(default)]
+    pub lights: Vec<Spanned<LightDescription>>,
+    #[serde(skip)]
+    source: Source,
+}
+
+impl SceneDescription {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SceneError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| SceneError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        Self::parse(&text, path)
+    }
+
+    /// `path` is only used for error messages and to resolve mesh files
+    pub fn parse(text: &str, path: impl AsRef<Path>) -> Result<Self, SceneError> {
+        let source = Source {
+            path: path.as_ref().to_owned(),
+            text: text.to_owned(),
+        };
+        let mut description: Self =
+            toml::from_str(text).map_err(|err| source.error(err.span(), err.message()))?;
+        description.source = source;
+        description.validate()?;
+        Ok(description)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("scene descriptions are always representable")
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_toml())
+    }
+
+    fn validate(&self) -> Result<(), SceneError> {
+        let camera = self.camera.get_ref();
+        if !(camera.vertical_fov > 0.0 && camera.vertical_fov < 180.0) {
+            return Err(self.source.error(
+                Some(self.camera.span()),
+                "vertical_fov must be between 0 and 180 degrees",
+            ));
+        }
+        let view = camera.lookfrom - camera.lookat;
+        if view.near_zero() {
+            return Err(self
+                .source
+                .error(Some(self.camera.span()), "lookfrom and lookat must differ"));
+        }
+        if camera.vup.cross(&view).near_zero() {
+            return Err(self.source.error(
+                Some(self.camera.span()),
+                "vup must not be parallel to the view direction",
+            ));
+        }
+        if camera.aperture < 0.0 || camera.aperture.is_nan() {
+            return Err(self
+                .source
+                .error(Some(self.camera.span()), "aperture must not be negative"));
+        }
+        if camera
+            .focus_dist
+            .is_some_and(|focus_dist| focus_dist <= 0.0 || focus_dist.is_nan())
+        {
+            return Err(self
+                .source
+                .error(Some(self.camera.span()), "focus_dist must be positive"));
+        }
+        if camera.shutter_close < camera.shutter_open {
+            return Err(self.source.error(
+                Some(self.camera.span()),
+                "shutter_close must not be before shutter_open",
+            ));
+        }
+        if let Some(background) = &self.background {
+            let error = |message: &str| Err(self.source.error(Some(background.span()), message));
+            match background.get_ref() {
+                BackgroundDescription::Map { intensity, .. }
+                | BackgroundDescription::Sky { intensity, .. }
+                    if *intensity < 0.0 || intensity.is_nan() =>
+                {
+                    return error("background intensity must not be negative")
+                }
+                BackgroundDescription::Sky { sun_elevation, .. }
+                    if !(0.0..=90.0).contains(sun_elevation) =>
+                {
+                    return error("sun_elevation must be between 0 and 90 degrees")
+                }
+                BackgroundDescription::Sky { turbidity, .. }
+                    if !(2.0..=10.0).contains(turbidity) =>
+                {
+                    return error("turbidity must be between 2 and 10")
+                }
+                _ => {}
+            }
+        }
+        if let Some(atmosphere) = &self.atmosphere {
+            let density = atmosphere.get_ref().density;
+            if density <= 0.0 || density.is_nan() {
+                return Err(self.source.error(
+                    Some(atmosphere.span()),
+                    "atmosphere density must be positive",
+                ));
+            }
+        }
+
+        for texture in self.textures.values() {
+            let error = |message: &str| Err(self.source.error(Some(texture.span()), message));
+            match texture.get_ref() {
+                TextureDescription::Checker { scale, .. }
+                | TextureDescription::Noise { scale, .. }
+                    if *scale <= 0.0 =>
+                {
+                    return error("texture scale must be positive")
+                }
+                _ => {}
+            }
+        }
+        for material in self.materials.values() {
+            let error = |message: &str| Err(self.source.error(Some(material.span()), message));
+            if let MaterialDescription::Lambertian {
+                albedo: AlbedoDescription::Texture(name),
+            }
+            | MaterialDescription::Metal {
+                albedo: AlbedoDescription::Texture(name),
+                ..
+            } = material.get_ref()
+            {
+                if !self.textures.contains_key(name) {
+                    return error(&format!("unknown texture '{name}'"));
+                }
+            }
+            match material.get_ref() {
+                MaterialDescription::Dielectric { refraction_index }
+                    if *refraction_index <= 0.0 =>
+                {
+                    return error("refraction_index must be positive")
+                }
+                MaterialDescription::Metal { fuzz, .. } if *fuzz < 0.0 => {
+                    return error("fuzz must not be negative")
+                }
+                MaterialDescription::Conductor { roughness, .. }
+                    if !roughness.pair().iter().all(|r| (0.0..=1.0).contains(r)) =>
+                {
+                    return error("roughness must be between 0 and 1")
+                }
+                MaterialDescription::Conductor {
+                    ior: IorDescription::Complex { eta, k },
+                    ..
+                } if !(0..3).all(|i| eta[i] > 0.0 && k[i] >= 0.0) => {
+                    return error("eta must be positive and k not negative")
+                }
+                MaterialDescription::Conductor { tangent, .. } if tangent.near_zero() => {
+                    return error("tangent must not be zero")
+                }
+                _ => {}
+            }
+        }
+        for object in &self.objects {
+            self.validate_object(object.get_ref(), object.span())?;
+        }
+        for light in &self.lights {
+            let error = |message: &str| Err(self.source.error(Some(light.span()), message));
+            match light.get_ref() {
+                LightDescription::Spot { direction, .. }
+                | LightDescription::Directional { direction, .. }
+                    if direction.near_zero() =>
+                {
+                    return error("light direction must not be zero")
+                }
+                LightDescription::Spot { cone_angle, .. }
+                    if !(*cone_angle > 0.0 && *cone_angle <= 180.0) =>
+                {
+                    return error("cone_angle must be between 0 and 180 degrees")
+                }
+                LightDescription::Spot {
+                    cone_angle,
+                    falloff_start: Some(falloff_start),
+                    ..
+                } if !(0.0..=*cone_angle).contains(falloff_start) => {
+                    return error("falloff_start must be between 0 and cone_angle")
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_object(
+        &self,
+        object: &ObjectDescription,
+        span: Range<usize>,
+    ) -> Result<(), SceneError> {
+        let error = |message: String| Err(self.source.error(Some(span.clone()), message));
+        let material = match object {
+            ObjectDescription::Sphere { radius, .. }
+            | ObjectDescription::MovingSphere { radius, .. }
+                if *radius == 0.0 =>
+            {
+                return error("sphere radius must not be zero".into())
+            }
+            ObjectDescription::Quad { u, v, .. } if u.cross(v).near_zero() => {
+                return error("quad edges must not be parallel".into())
+            }
+            ObjectDescription::Box { min, max, .. }
+                if (0..3).any(|axis| min[axis] == max[axis]) =>
+            {
+                return error("box must not be flat".into())
+            }
+            ObjectDescription::Instance {
+                transform,
+                transform_end,
+                object,
+            } => {
+                for step in transform.iter().chain(transform_end.iter().flatten()) {
+                    match step {
+                        TransformStep::Rotate { axis, .. } if axis.near_zero() => {
+                            return error("rotation axis must not be zero".into())
+                        }
+                        TransformStep::Scale(factors)
+                            if factors.x() * factors.y() * factors.z() == 0.0 =>
+                        {
+                            return error("scale factors must not be zero".into())
+                        }
+                        _ => {}
+                    }
+                }
+                if let Some(transform_end) = transform_end {
+                    if transform.len() != transform_end.len() {
+                        return error("transform_end must have as many steps as transform".into());
+                    }
+                    // Interpolated steps must stay invertible along the way
+                    for (i, (start, end)) in transform.iter().zip(transform_end).enumerate() {
+                        let step = i + 1;
+                        match (start, end) {
+                            (
+                                TransformStep::Rotate { axis, .. },
+                                TransformStep::Rotate { axis: axis_end, .. },
+                            ) if axis.dot(axis_end) <= 0.0 => {
+                                return error(format!(
+                                    "rotation axes of step {step} must not point apart"
+                                ))
+                            }
+                            (TransformStep::Scale(a), TransformStep::Scale(b))
+                                if a.x() * b.x() <= 0.0
+                                    || a.y() * b.y() <= 0.0
+                                    || a.z() * b.z() <= 0.0 =>
+                            {
+                                return error(format!(
+                                    "scale factors of step {step} must not change sign"
+                                ))
+                            }
+                            _ if start.lerp(end, 0.0).is_none() => {
+                                return error(format!(
+                                    "step {step} of transform_end is a different kind of step"
+                                ))
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                return self.validate_object(object, span);
+            }
+            ObjectDescription::ConstantMedium { density, .. }
+            | ObjectDescription::GridMedium { density, .. }
+                if *density <= 0.0 || density.is_nan() =>
+            {
+                return error("medium density must be positive".into())
+            }
+            ObjectDescription::ConstantMedium { anisotropy, .. }
+            | ObjectDescription::GridMedium { anisotropy, .. }
+                if anisotropy.abs() >= 1.0 || anisotropy.is_nan() =>
+            {
+                return error("medium anisotropy must be between -1 and 1".into())
+            }
+            ObjectDescription::ConstantMedium { boundary, .. } => {
+                return self.validate_object(boundary, span);
+            }
+            ObjectDescription::GridMedium { .. } => return Ok(()),
+            ObjectDescription::Sphere { material, .. }
+            | ObjectDescription::MovingSphere { material, .. }
+            | ObjectDescription::Triangle { material, .. }
+            | ObjectDescription::Quad { material, .. }
+            | ObjectDescription::Box { material, .. }
+            | ObjectDescription::Mesh { material, .. } => material,
+        };
+        if !self.materials.contains_key(material) {
+            return error(format!("unknown material '{material}'"));
+        }
+        Ok(())
+    }
+
+    /// The hittables making up `object`, each with its material
+    fn build_object(
+        &self,
+        object: &ObjectDescription,
+        span: Range<usize>,
+        materials: &BTreeMap<&str, Arc<dyn Material>>,
+    ) -> Result<Vec<Built>, SceneError> {
+        let material = |name: &String| materials[name.as_str()].clone();
+        let built: Vec<Built> = match object {
+            ObjectDescription::Sphere {
+                center,
+                radius,
+                material: name,
+            } => {
+                let material = material(name);
+                vec![(
+                    Arc::new(Sphere::new(*center, *radius, material.clone())),
+                    material,
+                )]
+            }
+            ObjectDescription::MovingSphere {
+                center0,
+                center1,
+                radius,
+                material: name,
+            } => {
+                let material = material(name);
+                vec![(
+                    Arc::new(MovingSphere::new(
+                        *center0,
+                        *center1,
+                        *radius,
+                        material.clone(),
+                    )),
+                    material,
+                )]
+            }
+            ObjectDescription::Triangle {
+                vertices: [a, b, c],
+                material: name,
+            } => {
+                let material = material(name);
+                vec![(
+                    Arc::new(Triangle::new(*a, *b, *c, material.clone())),
+                    material,
+                )]
+            }
+            ObjectDescription::Quad {
+                q,
+                u,
+                v,
+                material: name,
+            } => {
+                let material = material(name);
+                vec![(Arc::new(Quad::new(*q, *u, *v, material.clone())), material)]
+            }
+            ObjectDescription::Box {
+                min,
+                max,
+                material: name,
+            } => {
+                let material = material(name);
+                vec![(
+                    Arc::new(Cuboid::new(*min, *max, material.clone())),
+                    material,
+                )]
+            }
+            ObjectDescription::Mesh {
+                path,
+                material: name,
+            } => load_obj(self.directory().join(path), material(name))
+                .map_err(|err| {
+                    self.source
+                        .error(Some(span), format!("cannot load mesh: {err}"))
+                })?
+                .into_iter()
+                .map(|mesh| {
+                    let material = mesh.material().clone();
+                    (Arc::new(mesh) as Arc<dyn Hittable>, material)
+                })
+                .collect(),
+            ObjectDescription::Instance {
+                transform,
+                transform_end,
+                object,
+            } => self
+                .build_object(object, span.clone(), materials)?
+                .into_iter()
+                .map(|(object, material)| {
+                    let instance = match transform_end {
+                        Some(transform_end) => {
+                            Transformed::animated(object, transform.clone(), transform_end.clone())
+                        }
+                        None => Transformed::new(object, TransformStep::compose(transform)),
+                    };
+                    // Steps that are each invertible can still compose to a
+                    // transform too close to singular to invert reliably
+                    let instance = instance.ok_or_else(|| {
+                        self.source
+                            .error(Some(span.clone()), "instance transform must be invertible")
+                    })?;
+                    Ok((Arc::new(instance) as Arc<dyn Hittable>, material))
+                })
+                .collect::<Result<_, _>>()?,
+            ObjectDescription::ConstantMedium {
+                boundary,
+                density,
+                albedo,
+                anisotropy,
+            } => {
+                let mut pieces = self.build_object(boundary, span, materials)?;
+                let boundary: Arc<dyn Hittable> = if pieces.len() == 1 {
+                    pieces.remove(0).0
+                } else {
+                    let mut list = HitList::default();
+                    for (piece, _) in pieces {
+                        list.add(piece);
+                    }
+                    Arc::new(list)
+                };
+                let phase_function = phase_function(*albedo, *anisotropy);
+                vec![(
+                    Arc::new(ConstantMedium::new(
+                        boundary,
+                        *density,
+                        phase_function.clone(),
+                    )),
+                    phase_function,
+                )]
+            }
+            ObjectDescription::GridMedium {
+                path,
+                density,
+                albedo,
+                anisotropy,
+            } => {
+                let grid = load_vol(self.directory().join(path)).map_err(|err| {
+                    self.source
+                        .error(Some(span), format!("cannot load volume: {err}"))
+                })?;
+                let phase_function = phase_function(*albedo, *anisotropy);
+                vec![(
+                    Arc::new(GridMedium::new(
+                        Arc::new(grid),
+                        *density,
+                        phase_function.clone(),
+                    )),
+                    phase_function,
+                )]
+            }
+        };
+        Ok(built)
+    }
+
+    pub fn settings(&self) -> RenderSettings {
+        RenderSettings {
+            image_width: self.image.width.get(),
+            image_height: self.image.height.get(),
+            samples_per_pixel: self.render.samples_per_pixel.get(),
+            max_depth: self.render.max_depth,
+            min_bounces: self.render.min_bounces,
+        }
+    }
+
+    fn directory(&self) -> &Path {
+        self.source.path.parent().unwrap_or(Path::new(""))
+    }
+
+    /// Instantiates textures, materials and objects, loading any referenced
+    /// images and meshes
+    pub fn build(&self) -> Result<Scene, SceneError> {
+        let mut textures: BTreeMap<&str, Arc<dyn Texture>> = BTreeMap::new();
+        for (name, texture) in &self.textures {
+            let texture: Arc<dyn Texture> = match texture.get_ref() {
+                TextureDescription::Solid { color } => Arc::new(SolidColor::new(*color)),
+                TextureDescription::Checker { scale, even, odd } => {
+                    Arc::new(Checker::from_colors(*scale, *even, *odd))
+                }
+                TextureDescription::Image { path } => Arc::new(
+                    ImageTexture::load(self.directory().join(path)).map_err(|err| {
+                        self.source
+                            .error(Some(texture.span()), format!("cannot load image: {err}"))
+                    })?,
+                ),
+                TextureDescription::Noise { scale, color } => {
+                    Arc::new(NoiseTexture::new(*scale, *color))
+                }
+            };
+            textures.insert(name, texture);
+        }
+        let albedo = |albedo: &AlbedoDescription| -> Arc<dyn Texture> {
+            match albedo {
+                AlbedoDescription::Color(color) => Arc::new(SolidColor::new(*color)),
+                AlbedoDescription::Texture(name) => textures[name.as_str()].clone(),
+            }
+        };
+
+        let materials: BTreeMap<&str, Arc<dyn Material>> = self
+            .materials
+            .iter()
+            .map(|(name, material)| {
+                let material: Arc<dyn Material> = match material.get_ref() {
+                    MaterialDescription::Lambertian { albedo: a } => {
+                        Arc::new(Lambertian::textured(albedo(a)))
+                    }
+                    MaterialDescription::Metal { albedo: a, fuzz } => {
+                        Arc::new(Metal::textured(albedo(a), *fuzz))
+                    }
+                    MaterialDescription::Dielectric { refraction_index } => {
+                        Arc::new(Dielectric::new(*refraction_index))
+                    }
+                    MaterialDescription::Conductor {
+                        ior,
+                        roughness,
+                        tangent,
+                    } => {
+                        let (eta, k) = match ior {
+                            IorDescription::Preset(preset) => preset.ior(),
+                            IorDescription::Complex { eta, k } => (*eta, *k),
+                        };
+                        let [roughness_u, roughness_v] = roughness.pair();
+                        Arc::new(
+                            Conductor::new(eta, k, roughness_u, roughness_v).with_tangent(*tangent),
+                        )
+                    }
+                    MaterialDescription::DiffuseLight { emit, two_sided } => {
+                        Arc::new(DiffuseLight {
+                            emit: *emit,
+                            two_sided: *two_sided,
+                        })
+                    }
+                };
+                (name.as_str(), material)
+            })
+            .collect();
+        let mut world = HitList::default();
+        let mut emitters = HitList::default();
+        for object in &self.objects {
+            for (hittable, material) in
+                self.build_object(object.get_ref(), object.span(), &materials)?
+            {
+                if material.is_emissive() {
+                    emitters.add(hittable.clone());
+                }
+                world.add(hittable);
+            }
+        }
+
+        let settings = self.settings();
+        let camera = self.camera.get_ref();
+        let camera = Camera::new(
+            camera.lookfrom,
+            camera.lookat,
+            camera.vup,
+            camera.vertical_fov,
+            settings.aspect_ratio(),
+            camera.aperture,
+            camera
+                .focus_dist
+                .unwrap_or_else(|| (camera.lookfrom - camera.lookat).length()),
+        )
+        .with_shutter(camera.shutter_open, camera.shutter_close);
+        let environment: Arc<dyn Environment> = match &self.background {
+            None => Arc::new(Gradient::default()),
+            Some(background) => match background.get_ref() {
+                BackgroundDescription::Gradient { horizon, zenith } => {
+                    Arc::new(Gradient::new(*horizon, *zenith))
+                }
+                BackgroundDescription::Solid { color } => Arc::new(Uniform::new(*color)),
+                BackgroundDescription::Map {
+                    path,
+                    rotation,
+                    intensity,
+                } => Arc::new(
+                    EnvironmentMap::load(self.directory().join(path), *rotation, *intensity)
+                        .map_err(|err| {
+                            self.source.error(
+                                Some(background.span()),
+                                format!("cannot load environment map: {err}"),
+                            )
+                        })?,
+                ),
+                BackgroundDescription::Sky {
+                    sun_elevation,
+                    sun_azimuth,
+                    turbidity,
+                    intensity,
+                } => Arc::new(
+                    Sky::new(*sun_elevation, *sun_azimuth, *turbidity).with_intensity(*intensity),
+                ),
+            },
+        };
+        let lights = self
+            .lights
+            .iter()
+            .map(|light| -> Arc<dyn Light> {
+                match *light.get_ref() {
+                    LightDescription::Point {
+                        position,
+                        intensity,
+                    } => Arc::new(PointLight::new(position, intensity)),
+                    LightDescription::Spot {
+                        position,
+                        direction,
+                        intensity,
+                        cone_angle,
+                        falloff_start,
+                    } => Arc::new(SpotLight::new(
+                        position,
+                        direction,
+                        intensity,
+                        cone_angle,
+                        falloff_start.unwrap_or(cone_angle),
+                    )),
+                    LightDescription::Directional {
+                        direction,
+                        irradiance,
+                    } => Arc::new(DirectionalLight::new(direction, irradiance)),
+                }
+            })
+            .collect();
+        Ok(Scene {
+            world: world.into(),
+            emitters,
+            lights,
+            camera,
+            environment,
+            atmosphere: self.atmosphere.as_ref().map(|atmosphere| {
+                let atmosphere = atmosphere.get_ref();
+                Atmosphere::isotropic(atmosphere.density, atmosphere.albedo)
+            }),
+            settings,
+        })
+    }
+}
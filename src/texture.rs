@@ -0,0 +1,11 @@
+pub mod checker;
+pub mod image;
+pub mod noise;
+pub mod solid;
+
+use crate::common::*;
+
+pub trait Texture: std::fmt::Debug + Send + Sync {
+    /// Color at surface coordinates (`u`, `v`) of the hit at `point`
+    fn value(&self, u: f64, v: f64, point: &Point) -> Color;
+}
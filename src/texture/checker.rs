@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use crate::{
+    common::*,
+    texture::{solid::SolidColor, Texture},
+};
+
+/// 3D checkerboard in world space, alternating cubes of side `scale`
+#[derive(Debug)]
+pub struct Checker {
+    inv_scale: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl Checker {
+    pub fn new(scale: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> Self {
+        Self {
+            inv_scale: 1.0 / scale,
+            even,
+            odd,
+        }
+    }
+
+    pub fn from_colors(scale: f64, even: Color, odd: Color) -> Self {
+        Self::new(
+            scale,
+            Arc::new(SolidColor::new(even)),
+            Arc::new(SolidColor::new(odd)),
+        )
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f64, v: f64, point: &Point) -> Color {
+        let cell = |x: f64| (self.inv_scale * x).floor() as i64;
+        if (cell(point.x()) + cell(point.y()) + cell(point.z())) % 2 == 0 {
+            self.even.value(u, v, point)
+        } else {
+            self.odd.value(u, v, point)
+        }
+    }
+}
@@ -0,0 +1,380 @@
+use std::{
+    fmt::Display,
+    fs::{self, File},
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{common::*, texture::Texture};
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io { path: PathBuf, source: io::Error },
+    Format { path: PathBuf, message: String },
+}
+
+impl Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "cannot read {}: {source}", path.display()),
+            Self::Format { path, message } => write!(f, "{}: {message}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Format { .. } => None,
+        }
+    }
+}
+
+/// Inverse of the sRGB transfer function, for 8-bit image data
+pub fn srgb_to_linear(value: u8) -> f64 {
+    let c = value as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Linear RGB image, stored top row first
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Color>,
+}
+
+impl Image {
+    /// Loads a PNG or PPM (P3/P6) file, converted from sRGB, or a Radiance
+    /// HDR or OpenEXR file, which are already linear, chosen by extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ImageError> {
+        let path = path.as_ref();
+        let format_error = |message: String| ImageError::Format {
+            path: path.to_owned(),
+            message,
+        };
+        let io_error = |source| ImageError::Io {
+            path: path.to_owned(),
+            source,
+        };
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("png") => {
+                let file = File::open(path).map_err(io_error)?;
+                load_png(BufReader::new(file)).map_err(format_error)
+            }
+            Some("ppm") => {
+                let data = fs::read(path).map_err(io_error)?;
+                load_ppm(&data).map_err(format_error)
+            }
+            Some("hdr") => {
+                let data = fs::read(path).map_err(io_error)?;
+                load_hdr(&data).map_err(format_error)
+            }
+            Some("exr") => load_exr(path).map_err(format_error),
+            _ => Err(format_error("unsupported image format".into())),
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+}
+
+fn load_png(reader: BufReader<File>) -> Result<Image, String> {
+    let mut decoder = png::Decoder::new(reader);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+    let mut buf = vec![0; reader.output_buffer_size().ok_or("image too large")?];
+    let info = reader.next_frame(&mut buf).map_err(|err| err.to_string())?;
+    let channels = info.color_type.samples();
+    let pixels = buf[..info.buffer_size()]
+        .chunks_exact(channels)
+        .map(|px| match px {
+            [gray] | [gray, _] => {
+                let value = srgb_to_linear(*gray);
+                Color::new(value, value, value)
+            }
+            [r, g, b, ..] => Color::new(srgb_to_linear(*r), srgb_to_linear(*g), srgb_to_linear(*b)),
+            [] => unreachable!(),
+        })
+        .collect();
+    Ok(Image {
+        width: info.width as usize,
+        height: info.height as usize,
+        pixels,
+    })
+}
+
+/// Tokenizer for the whitespace separated, `#`-commented PPM header
+struct PpmReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl PpmReader<'_> {
+    fn token(&mut self) -> Result<&[u8], String> {
+        let data = self.data;
+        loop {
+            while self.pos < data.len() && data[self.pos].is_ascii_whitespace() {
+                self.pos += 1;
+            }
+            if self.pos < data.len() && data[self.pos] == b'#' {
+                while self.pos < data.len() && data[self.pos] != b'\n' {
+                    self.pos += 1;
+                }
+                continue;
+            }
+            break;
+        }
+        let start = self.pos;
+        while self.pos < data.len() && !data[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err("unexpected end of file".into());
+        }
+        Ok(&data[start..self.pos])
+    }
+
+    fn number(&mut self) -> Result<usize, String> {
+        let token = self.token()?;
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|token| token.parse().ok())
+            .ok_or_else(|| format!("invalid number '{}'", String::from_utf8_lossy(token)))
+    }
+}
+
+fn load_ppm(data: &[u8]) -> Result<Image, String> {
+    let mut reader = PpmReader { data, pos: 0 };
+    let magic = reader.token()?.to_vec();
+    let width = reader.number()?;
+    let height = reader.number()?;
+    let max_value = reader.number()?;
+    if max_value == 0 || max_value > 255 {
+        return Err(format!("unsupported maximum value {max_value}"));
+    }
+    let scale = |value: usize| srgb_to_linear((value.min(max_value) * 255 / max_value) as u8);
+    let count = width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(3))
+        .ok_or("image is too large")?;
+
+    let samples: Vec<usize> = match magic.as_slice() {
+        b"P3" => (0..count)
+            .map(|_| reader.number())
+            .collect::<Result<_, _>>()?,
+        b"P6" => {
+            // Exactly one whitespace byte separates the header from the raster
+            let start = reader.pos + 1;
+            let raster = start
+                .checked_add(count)
+                .and_then(|end| data.get(start..end))
+                .ok_or("truncated raster")?;
+            raster.iter().map(|&b| b as usize).collect()
+        }
+        _ => return Err("not a P3 or P6 PPM file".into()),
+    };
+    let pixels = samples
+        .chunks_exact(3)
+        .map(|rgb| Color::new(scale(rgb[0]), scale(rgb[1]), scale(rgb[2])))
+        .collect();
+    Ok(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Radiance RGBE, flat or with run-length encoded scanlines, in the usual
+/// top-to-bottom, left-to-right orientation
+fn load_hdr(data: &[u8]) -> Result<Image, String> {
+    let mut pos = 0;
+    let mut line = || -> Result<&str, String> {
+        let end = data[pos..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or("truncated header")?;
+        let line = std::str::from_utf8(&data[pos..pos + end]).map_err(|_| "invalid header")?;
+        pos += end + 1;
+        Ok(line)
+    };
+    if !line()?.starts_with("#?") {
+        return Err("not a Radiance HDR file".into());
+    }
+    loop {
+        let header = line()?;
+        if header.is_empty() {
+            break;
+        }
+        if let Some(format) = header.strip_prefix("FORMAT=") {
+            if format != "32-bit_rle_rgbe" {
+                return Err(format!("unsupported format {format}"));
+            }
+        }
+    }
+    let resolution = line()?;
+    let (height, width) = match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+        ["-Y", height, "+X", width] => (height.parse().ok(), width.parse().ok()),
+        _ => (None, None),
+    };
+    let (Some(height), Some(width)): (Option<usize>, Option<usize>) = (height, width) else {
+        return Err(format!("unsupported resolution line '{resolution}'"));
+    };
+    if width == 0 || height == 0 {
+        return Err("image has no pixels".into());
+    }
+
+    // Scanlines of these widths may be run-length encoded, otherwise they
+    // are flat
+    let rle_width = (8..0x8000).contains(&width);
+    let (Some(count), Some(row_size)) = (
+        width.checked_mul(height),
+        if rle_width {
+            Some(4 + 8 * width.div_ceil(127))
+        } else {
+            width.checked_mul(4)
+        },
+    ) else {
+        return Err("image is too large".into());
+    };
+    let mut data = &data[pos..];
+    // Checked before allocating: an encoded scanline takes at least two bytes
+    // per channel for every 127 pixels, the longest run
+    if row_size
+        .checked_mul(height)
+        .is_none_or(|size| size > data.len())
+    {
+        return Err("truncated raster".into());
+    }
+    let mut rgbe = vec![[0u8; 4]; count];
+    let mut take = |count: usize| -> Result<&[u8], String> {
+        if data.len() < count {
+            return Err("truncated raster".into());
+        }
+        let (head, tail) = data.split_at(count);
+        data = tail;
+        Ok(head)
+    };
+    for row in rgbe.chunks_exact_mut(width) {
+        let rle = rle_width && {
+            let start = take(4)?;
+            if start[0] == 2 && start[1] == 2 && start[2] & 0x80 == 0 {
+                if ((start[2] as usize) << 8 | start[3] as usize) != width {
+                    return Err("scanline width mismatch".into());
+                }
+                true
+            } else {
+                row[0].copy_from_slice(start);
+                false
+            }
+        };
+        if rle {
+            // Each channel is stored separately as runs and literal spans
+            for channel in 0..4 {
+                let mut x = 0;
+                while x < width {
+                    let count = take(1)?[0] as usize;
+                    let (count, run) = if count > 128 {
+                        (count - 128, true)
+                    } else {
+                        (count, false)
+                    };
+                    if count == 0 || x + count > width {
+                        return Err("corrupt run-length encoding".into());
+                    }
+                    if run {
+                        let value = take(1)?[0];
+                        row[x..x + count]
+                            .iter_mut()
+                            .for_each(|px| px[channel] = value);
+                    } else {
+                        let values = take(count)?;
+                        for (px, &value) in row[x..x + count].iter_mut().zip(values) {
+                            px[channel] = value;
+                        }
+                    }
+                    x += count;
+                }
+            }
+        } else {
+            let first = usize::from(rle_width);
+            for px in &mut row[first..] {
+                px.copy_from_slice(take(4)?);
+            }
+        }
+    }
+    let pixels = rgbe
+        .iter()
+        .map(|&[r, g, b, e]| {
+            if e == 0 {
+                return Color::black();
+            }
+            let scale = 2f64.powi(e as i32 - 136);
+            Color::new(r as f64 * scale, g as f64 * scale, b as f64 * scale)
+        })
+        .collect();
+    Ok(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// The first layer with RGB channels of an OpenEXR file
+fn load_exr(path: &Path) -> Result<Image, String> {
+    let image = exr::prelude::read_first_rgba_layer_from_file(
+        path,
+        |resolution, _| Image {
+            width: resolution.width(),
+            height: resolution.height(),
+            pixels: vec![Color::black(); resolution.area()],
+        },
+        |image: &mut Image, position, (r, g, b, _): (f32, f32, f32, f32)| {
+            let index = position.y() * image.width + position.x();
+            image.pixels[index] = Color::new(r as f64, g as f64, b as f64);
+        },
+    )
+    .map_err(|err| err.to_string())?;
+    Ok(image.layer_data.channel_data.pixels)
+}
+
+/// Maps an image over the (u, v) unit square with v pointing up, clamping at the edges
+#[derive(Debug)]
+pub struct ImageTexture {
+    image: Arc<Image>,
+}
+
+impl ImageTexture {
+    pub fn new(image: Arc<Image>) -> Self {
+        Self { image }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ImageError> {
+        Ok(Self::new(Arc::new(Image::load(path)?)))
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _point: &Point) -> Color {
+        let image = &self.image;
+        if image.pixels.is_empty() {
+            return Color::new(0.0, 1.0, 1.0);
+        }
+        let u = u.clamp(0.0, 1.0);
+        let v = 1.0 - v.clamp(0.0, 1.0);
+        let x = ((u * image.width as f64) as usize).min(image.width - 1);
+        let y = ((v * image.height as f64) as usize).min(image.height - 1);
+        image.pixel(x, y)
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{common::*, texture::Texture};
+
+const POINT_COUNT: usize = 256;
+
+/// Ken Perlin's gradient noise over random unit vectors at the lattice points
+#[derive(Debug)]
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Default for Perlin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Perlin {
+    pub fn new() -> Self {
+        Self {
+            gradients: (0..POINT_COUNT)
+                .map(|_| Vec3::random(-1.0, 1.0).unit_vec())
+                .collect(),
+            perm_x: Self::permutation(),
+            perm_y: Self::permutation(),
+            perm_z: Self::permutation(),
+        }
+    }
+
+    fn permutation() -> Vec<usize> {
+        let mut perm: Vec<usize> = (0..POINT_COUNT).collect();
+        for i in (1..POINT_COUNT).rev() {
+            let target = ((random::unit() * (i + 1) as f64) as usize).min(i);
+            perm.swap(i, target);
+        }
+        perm
+    }
+
+    /// Noise in roughly [-1, 1]
+    pub fn noise(&self, point: &Point) -> f64 {
+        let (fx, fy, fz) = (point.x().floor(), point.y().floor(), point.z().floor());
+        let (u, v, w) = (point.x() - fx, point.y() - fy, point.z() - fz);
+        let (i, j, k) = (fx as i64, fy as i64, fz as i64);
+
+        let wrap = |n: i64, d: usize| ((n + d as i64) & (POINT_COUNT as i64 - 1)) as usize;
+        let mut corners = [[[Vec3::default(); 2]; 2]; 2];
+        for (di, plane) in corners.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, corner) in row.iter_mut().enumerate() {
+                    *corner = self.gradients[self.perm_x[wrap(i, di)]
+                        ^ self.perm_y[wrap(j, dj)]
+                        ^ self.perm_z[wrap(k, dk)]];
+                }
+            }
+        }
+
+        // Hermite smoothing hides the lattice
+        let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+        let (uu, vv, ww) = (smooth(u), smooth(v), smooth(w));
+        let mut accum = 0.0;
+        for (di, plane) in corners.iter().enumerate() {
+            for (dj, row) in plane.iter().enumerate() {
+                for (dk, gradient) in row.iter().enumerate() {
+                    let (fi, fj, fk) = (di as f64, dj as f64, dk as f64);
+                    let weight = Vec3::new(u - fi, v - fj, w - fk);
+                    accum += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                        * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                        * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                        * gradient.dot(&weight);
+                }
+            }
+        }
+        accum
+    }
+
+    /// Sum of `depth` octaves of noise with halving amplitude
+    pub fn turbulence(&self, point: &Point, depth: usize) -> f64 {
+        let mut accum = 0.0;
+        let mut point = *point;
+        let mut weight = 1.0;
+        for _ in 0..depth {
+            accum += weight * self.noise(&point);
+            weight *= 0.5;
+            point = point * 2.0;
+        }
+        accum.abs()
+    }
+}
+
+/// Marble-like pattern: sine stripes along z phase-shifted by turbulence
+#[derive(Debug)]
+pub struct NoiseTexture {
+    noise: Perlin,
+    scale: f64,
+    color: Color,
+}
+
+impl NoiseTexture {
+    pub fn new(scale: f64, color: Color) -> Self {
+        Self {
+            noise: Perlin::new(),
+            scale,
+            color,
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, point: &Point) -> Color {
+        let phase = self.scale * point.z() + 10.0 * self.noise.turbulence(point, 7);
+        self.color * 0.5 * (1.0 + phase.sin())
+    }
+}
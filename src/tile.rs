@@ -0,0 +1,191 @@
+use std::{
+    fmt::Display,
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Rectangle of film pixels, `x0..x1` by `y0..y1` with the top row first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub x0: usize,
+    pub y0: usize,
+    pub x1: usize,
+    pub y1: usize,
+}
+
+impl Tile {
+    pub fn width(&self) -> usize {
+        self.x1 - self.x0
+    }
+
+    pub fn height(&self) -> usize {
+        self.y1 - self.y0
+    }
+
+    pub fn pixels(&self) -> impl Iterator<Item = (usize, usize)> {
+        let Tile { x0, y0, x1, y1 } = *self;
+        (y0..y1).flat_map(move |y| (x0..x1).map(move |x| (x, y)))
+    }
+}
+
+/// Order in which tiles are handed out to workers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileOrder {
+    /// Row by row from the top left
+    Scanline,
+    /// Outwards from the center of the image
+    Spiral,
+    /// Along a Hilbert curve, keeping consecutive tiles adjacent
+    Hilbert,
+}
+
+impl TileOrder {
+    pub const ALL: &'static [TileOrder] =
+        &[TileOrder::Scanline, TileOrder::Spiral, TileOrder::Hilbert];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TileOrder::Scanline => "scanline",
+            TileOrder::Spiral => "spiral",
+            TileOrder::Hilbert => "hilbert",
+        }
+    }
+
+    /// Grid cells of a `columns` by `rows` grid in this order
+    fn cells(&self, columns: usize, rows: usize) -> Vec<(usize, usize)> {
+        match self {
+            TileOrder::Scanline => (0..rows)
+                .flat_map(|row| (0..columns).map(move |column| (column, row)))
+                .collect(),
+            TileOrder::Spiral => spiral(columns, rows),
+            TileOrder::Hilbert => {
+                let side = columns.max(rows).next_power_of_two();
+                let mut cells: Vec<_> = (0..rows)
+                    .flat_map(|row| (0..columns).map(move |column| (column, row)))
+                    .collect();
+                cells.sort_by_key(|&(column, row)| hilbert_index(side, column, row));
+                cells
+            }
+        }
+    }
+}
+
+/// Walks right, down, left, up with growing leg lengths from the center cell,
+/// keeping the cells that fall inside the grid
+fn spiral(columns: usize, rows: usize) -> Vec<(usize, usize)> {
+    let total = columns * rows;
+    let mut cells = Vec::with_capacity(total);
+    if total == 0 {
+        return cells;
+    }
+    let (mut x, mut y) = (((columns - 1) / 2) as isize, ((rows - 1) / 2) as isize);
+    let directions = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+    let mut leg = 1;
+    let mut direction = 0;
+    cells.push((x as usize, y as usize));
+    while cells.len() < total {
+        // Each leg length is walked twice before growing
+        for _ in 0..2 {
+            let (dx, dy) = directions[direction];
+            for _ in 0..leg {
+                x += dx;
+                y += dy;
+                if (0..columns as isize).contains(&x) && (0..rows as isize).contains(&y) {
+                    cells.push((x as usize, y as usize));
+                }
+            }
+            direction = (direction + 1) % 4;
+        }
+        leg += 1;
+    }
+    cells
+}
+
+/// Distance along the Hilbert curve filling a `side` by `side` grid
+fn hilbert_index(side: usize, mut x: usize, mut y: usize) -> usize {
+    let mut index = 0;
+    let mut s = side / 2;
+    while s > 0 {
+        let rx = usize::from(x & s > 0);
+        let ry = usize::from(y & s > 0);
+        index += s * s * ((3 * rx) ^ ry);
+        // Rotate the quadrant so the curve stays continuous
+        if ry == 0 {
+            if rx == 1 {
+                x = side - 1 - x;
+                y = side - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    index
+}
+
+impl Display for TileOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for TileOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|order| order.name() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(TileOrder::name).collect();
+                format!(
+                    "unknown tile order '{s}', expected one of: {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Tiles covering the image, shared between workers that each pull the next
+/// unclaimed tile until none are left
+#[derive(Debug)]
+pub struct TileQueue {
+    tiles: Vec<Tile>,
+    next: AtomicUsize,
+}
+
+impl TileQueue {
+    pub fn new(width: usize, height: usize, tile_size: usize, order: TileOrder) -> Self {
+        assert!(tile_size > 0, "tile size must be positive");
+        let columns = width.div_ceil(tile_size);
+        let rows = height.div_ceil(tile_size);
+        let tiles = order
+            .cells(columns, rows)
+            .into_iter()
+            .map(|(column, row)| Tile {
+                x0: column * tile_size,
+                y0: row * tile_size,
+                x1: ((column + 1) * tile_size).min(width),
+                y1: ((row + 1) * tile_size).min(height),
+            })
+            .collect();
+        Self {
+            tiles,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Claims the next tile, or `None` once all have been handed out
+    pub fn claim(&self) -> Option<Tile> {
+        let index = self.next.fetch_add(1, Ordering::Relaxed);
+        self.tiles.get(index).copied()
+    }
+}
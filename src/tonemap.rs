@@ -0,0 +1,144 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::common::*;
+
+/// Operators compressing linear radiance into [0, 1] for display
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToneMapper {
+    /// Hard clip at 1
+    Clamp,
+    /// L / (1 + L) on luminance
+    Reinhard,
+    /// Reinhard that maps luminance `white_point` to 1 instead of infinity
+    ExtendedReinhard { white_point: f64 },
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve
+    Aces,
+    /// John Hable's Uncharted 2 filmic curve
+    Hable,
+}
+
+impl ToneMapper {
+    pub const NAMES: &'static [&'static str] =
+        &["clamp", "reinhard", "extended-reinhard", "aces", "hable"];
+    pub const DEFAULT_WHITE_POINT: f64 = 4.0;
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToneMapper::Clamp => "clamp",
+            ToneMapper::Reinhard => "reinhard",
+            ToneMapper::ExtendedReinhard { .. } => "extended-reinhard",
+            ToneMapper::Aces => "aces",
+            ToneMapper::Hable => "hable",
+        }
+    }
+
+    pub fn apply(&self, color: &Color) -> Color {
+        match *self {
+            ToneMapper::Clamp => Color::new(
+                color.x().clamp(0.0, 1.0),
+                color.y().clamp(0.0, 1.0),
+                color.z().clamp(0.0, 1.0),
+            ),
+            ToneMapper::Reinhard => scale_luminance(color, |l| l / (1.0 + l)),
+            ToneMapper::ExtendedReinhard { white_point } => {
+                let white_squared = white_point * white_point;
+                scale_luminance(color, |l| l * (1.0 + l / white_squared) / (1.0 + l))
+            }
+            ToneMapper::Aces => map_channels(color, |x| {
+                (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+            }),
+            ToneMapper::Hable => {
+                const EXPOSURE_BIAS: f64 = 2.0;
+                const WHITE: f64 = 11.2;
+                let curve = |x: f64| {
+                    let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+                    ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f
+                };
+                let white_scale = 1.0 / curve(WHITE);
+                map_channels(color, |x| curve(EXPOSURE_BIAS * x) * white_scale)
+            }
+        }
+    }
+}
+
+fn map_channels(color: &Color, f: impl Fn(f64) -> f64) -> Color {
+    let f = |x: f64| f(x.max(0.0)).clamp(0.0, 1.0);
+    Color::new(f(color.x()), f(color.y()), f(color.z()))
+}
+
+/// Scales the color so its luminance follows `f`, preserving hue
+fn scale_luminance(color: &Color, f: impl Fn(f64) -> f64) -> Color {
+    let luminance = color.luminance();
+    if luminance <= 0.0 {
+        return Color::black();
+    }
+    let mapped = *color * (f(luminance) / luminance);
+    map_channels(&mapped, |x| x)
+}
+
+impl Display for ToneMapper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ToneMapper {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clamp" => Ok(ToneMapper::Clamp),
+            "reinhard" => Ok(ToneMapper::Reinhard),
+            "extended-reinhard" => Ok(ToneMapper::ExtendedReinhard {
+                white_point: Self::DEFAULT_WHITE_POINT,
+            }),
+            "aces" => Ok(ToneMapper::Aces),
+            "hable" | "uncharted" => Ok(ToneMapper::Hable),
+            _ => Err(format!(
+                "unknown tone mapper '{s}', expected one of: {}",
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// The sRGB opto-electronic transfer function, for values in [0, 1]
+pub fn linear_to_srgb(value: f64) -> f64 {
+    if value <= 0.0031308 {
+        12.92 * value
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Display transform from linear radiance to 8-bit sRGB
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMapping {
+    pub operator: ToneMapper,
+    /// Exposure compensation in stops, applied before the operator
+    pub exposure: f64,
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        Self {
+            operator: ToneMapper::Clamp,
+            exposure: 0.0,
+        }
+    }
+}
+
+impl ToneMapping {
+    pub fn map(&self, radiance: &Color) -> [u8; 3] {
+        fn translate_rgb_to_int(value: f64) -> u8 {
+            (value.clamp(0.0, 0.999) * 256.0) as u8
+        }
+        let exposed = *radiance * 2f64.powf(self.exposure);
+        let mapped = self.operator.apply(&exposed);
+        [
+            translate_rgb_to_int(linear_to_srgb(mapped.x())),
+            translate_rgb_to_int(linear_to_srgb(mapped.y())),
+            translate_rgb_to_int(linear_to_srgb(mapped.z())),
+        ]
+    }
+}
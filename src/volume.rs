@@ -0,0 +1,188 @@
+//! Dense voxel grids for heterogeneous media, read from Mitsuba's binary
+//! `.vol` format: the bytes `VOL` and version 3, then little-endian `i32`
+//! encoding (1 for `f32`), x, y and z resolution and channel count (1), the
+//! bounding box as six `f32`s (min then max) and finally the densities with x
+//! varying fastest.
+
+use std::{
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::common::*;
+
+#[derive(Debug)]
+pub enum VolumeError {
+    Io { path: PathBuf, source: io::Error },
+    Format { path: PathBuf, message: String },
+}
+
+impl Display for VolumeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "cannot read {}: {source}", path.display()),
+            Self::Format { path, message } => write!(f, "{}: {message}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for VolumeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Format { .. } => None,
+        }
+    }
+}
+
+/// Densities sampled at the corners of a regular grid of cells spanning
+/// `bounds`, trilinearly interpolated in between
+#[derive(Debug, Clone)]
+pub struct VoxelGrid {
+    resolution: [usize; 3],
+    bounds: Aabb,
+    densities: Vec<f32>,
+    max_density: f64,
+}
+
+impl VoxelGrid {
+    /// `densities` lists x fastest, then y, then z. Panics if its length
+    /// doesn't match `resolution` or any axis has fewer than two samples.
+    pub fn new(resolution: [usize; 3], bounds: Aabb, densities: Vec<f32>) -> Self {
+        assert!(
+            resolution.iter().all(|&n| n >= 2),
+            "voxel grids need at least two samples per axis"
+        );
+        let count = resolution
+            .iter()
+            .try_fold(1_usize, |count, &n| count.checked_mul(n));
+        assert_eq!(
+            Some(densities.len()),
+            count,
+            "voxel count must match the grid resolution"
+        );
+        let max_density = densities.iter().fold(0.0_f64, |max, &d| max.max(d as f64));
+        Self {
+            resolution,
+            bounds,
+            densities,
+            max_density,
+        }
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        self.bounds
+    }
+
+    /// Upper bound of `density` anywhere in the grid
+    pub fn max_density(&self) -> f64 {
+        self.max_density
+    }
+
+    fn at(&self, x: usize, y: usize, z: usize) -> f64 {
+        let [nx, ny, _] = self.resolution;
+        self.densities[(z * ny + y) * nx + x] as f64
+    }
+
+    /// Interpolated density at `point`, zero outside the grid
+    pub fn density(&self, point: &Point) -> f64 {
+        let extent = self.bounds.diagonal();
+        let mut cell = [0; 3];
+        let mut weight = [0.0; 3];
+        for axis in 0..3 {
+            let n = self.resolution[axis];
+            let offset = (point[axis] - self.bounds.min[axis]) / extent[axis];
+            if !(0.0..=1.0).contains(&offset) {
+                return 0.0;
+            }
+            let position = offset * (n - 1) as f64;
+            cell[axis] = (position as usize).min(n - 2);
+            weight[axis] = position - cell[axis] as f64;
+        }
+        let [x, y, z] = cell;
+        let [wx, wy, wz] = weight;
+        let lerp = |a: f64, b: f64, t: f64| a + t * (b - a);
+        let along_x = |y, z| lerp(self.at(x, y, z), self.at(x + 1, y, z), wx);
+        let along_y = |z| lerp(along_x(y, z), along_x(y + 1, z), wy);
+        lerp(along_y(z), along_y(z + 1), wz)
+    }
+}
+
+/// Loads a single channel `f32` grid in Mitsuba's `.vol` format
+pub fn load_vol(path: impl AsRef<Path>) -> Result<VoxelGrid, VolumeError> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|source| VolumeError::Io {
+        path: path.to_owned(),
+        source,
+    })?;
+    let error = |message: String| VolumeError::Format {
+        path: path.to_owned(),
+        message,
+    };
+
+    if bytes.len() < 48 || &bytes[..3] != b"VOL" {
+        return Err(error("not a .vol file".into()));
+    }
+    if bytes[3] != 3 {
+        return Err(error(format!("unsupported version {}", bytes[3])));
+    }
+    let word =
+        |index: usize| -> [u8; 4] { bytes[4 + 4 * index..8 + 4 * index].try_into().unwrap() };
+    let int = |index: usize| i32::from_le_bytes(word(index));
+    let float = |index: usize| f32::from_le_bytes(word(index)) as f64;
+
+    if int(0) != 1 {
+        return Err(error(format!(
+            "unsupported encoding {}, expected 1 (f32)",
+            int(0)
+        )));
+    }
+    if int(4) != 1 {
+        return Err(error(format!(
+            "expected a single density channel, found {}",
+            int(4)
+        )));
+    }
+    let mut resolution = [0; 3];
+    for (axis, n) in resolution.iter_mut().enumerate() {
+        *n = usize::try_from(int(1 + axis))
+            .ok()
+            .filter(|&n| n >= 2)
+            .ok_or_else(|| error(format!("invalid resolution {}", int(1 + axis))))?;
+    }
+    let bounds = Aabb {
+        min: Point::new(float(5), float(6), float(7)),
+        max: Point::new(float(8), float(9), float(10)),
+    };
+    let extent = bounds.diagonal();
+    if (0..3).any(|axis| !(extent[axis].is_finite() && extent[axis] > 0.0)) {
+        return Err(error("bounding box is empty".into()));
+    }
+
+    let [nx, ny, nz] = resolution;
+    let Some(length) = resolution
+        .iter()
+        .try_fold(4_usize, |length, &n| length.checked_mul(n))
+    else {
+        return Err(error(format!(
+            "grid of {nx}x{ny}x{nz} densities is too large"
+        )));
+    };
+    let count = length / 4;
+    let data = &bytes[48..];
+    if data.len() != length {
+        return Err(error(format!(
+            "expected {count} densities for a {nx}x{ny}x{nz} grid, found {} bytes",
+            data.len()
+        )));
+    }
+    let densities: Vec<f32> = data
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect();
+    if densities.iter().any(|d| !(d.is_finite() && *d >= 0.0)) {
+        return Err(error("densities must be finite and not negative".into()));
+    }
+    Ok(VoxelGrid::new(resolution, bounds, densities))
+}